@@ -7,7 +7,7 @@ use reth_primitives::ChainSpec;
 /// [PayloadAttributes] trait for ethereum mainnet and optimism types.
 pub mod traits;
 use serde::{de::DeserializeOwned, ser::Serialize};
-pub use traits::{BuiltPayload, PayloadAttributes, PayloadBuilderAttributes};
+pub use traits::{BuiltPayload, PayloadAttributes, PayloadBuilderAttributes, PayloadKind};
 
 /// Contains error types used in the traits defined in this crate.
 pub mod error;
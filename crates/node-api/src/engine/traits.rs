@@ -18,6 +18,31 @@ pub trait BuiltPayload: Send + Sync + std::fmt::Debug {
 
     /// Returns the fees collected for the built block
     fn fees(&self) -> U256;
+
+    /// Returns the kind of the built block.
+    ///
+    /// A block without transactions isn't necessarily empty, it can still apply withdrawals.
+    fn kind(&self) -> PayloadKind {
+        let block = self.block();
+        if !block.body.is_empty() {
+            PayloadKind::Full
+        } else if block.withdrawals.as_ref().is_some_and(|withdrawals| !withdrawals.is_empty()) {
+            PayloadKind::EmptyWithWithdrawals
+        } else {
+            PayloadKind::Empty
+        }
+    }
+}
+
+/// The kind of a [BuiltPayload], by the contents of its block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// A block without transactions and withdrawals.
+    Empty,
+    /// A block without transactions that applies withdrawals.
+    EmptyWithWithdrawals,
+    /// A block with transactions.
+    Full,
 }
 
 /// This can be implemented by types that describe a currently running payload job.
@@ -62,6 +87,13 @@ pub trait PayloadBuilderAttributes: Send + Sync + std::fmt::Debug {
     /// Returns the withdrawals for the running payload job.
     fn withdrawals(&self) -> &Withdrawals;
 
+    /// Returns the block gas limit requested for the running payload job, if any.
+    ///
+    /// Overrides the gas limit derived from the parent block. Defaults to `None`.
+    fn gas_limit(&self) -> Option<u64> {
+        None
+    }
+
     /// Returns the configured [CfgEnvWithHandlerCfg] and [BlockEnv] for the targeted payload (that
     /// has the `parent` as its parent).
     ///
@@ -15,8 +15,8 @@ pub mod engine;
 pub use engine::{
     validate_payload_timestamp, validate_version_specific_fields, validate_withdrawals_presence,
     BuiltPayload, EngineApiMessageVersion, EngineObjectValidationError, EngineTypes,
-    MessageValidationKind, PayloadAttributes, PayloadBuilderAttributes, PayloadOrAttributes,
-    VersionSpecificValidationError,
+    MessageValidationKind, PayloadAttributes, PayloadBuilderAttributes, PayloadKind,
+    PayloadOrAttributes, VersionSpecificValidationError,
 };
 
 /// Traits and helper types used to abstract over EVM methods and types.
@@ -0,0 +1,158 @@
+//! A [PayloadBuilder] that alternates between two builders.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder, PayloadConfig};
+use reth_payload_builder::{database::CachedReads, error::PayloadBuilderError, PayloadId};
+use reth_primitives::{AccessList, Hardfork};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// A [PayloadBuilder] that alternates between two builders on consecutive build attempts.
+///
+/// The first builder is used for the first attempt of a job and every other attempt after it, the
+/// second builder for the remaining ones. The job keeps the better payload of both, which allows
+/// comparing two strategies within one slot.
+///
+/// The empty payload and the payload on missing payload are built by the first builder.
+///
+/// The attempt counter is shared by all clones, because the job clones its builder for every
+/// build.
+#[derive(Debug, Clone)]
+pub struct AlternatingBuilder<A, B> {
+    /// The builder used for even attempts.
+    first: A,
+    /// The builder used for odd attempts.
+    second: B,
+    /// The number of build attempts of the current job.
+    attempts: Arc<AtomicUsize>,
+}
+
+impl<A, B> AlternatingBuilder<A, B> {
+    /// Creates a new [AlternatingBuilder] that starts with the `first` builder.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second, attempts: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// Returns the builder used for even attempts.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns the builder used for odd attempts.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<Pool, Client, A, B> PayloadBuilder<Pool, Client> for AlternatingBuilder<A, B>
+where
+    A: PayloadBuilder<Pool, Client>,
+    B: PayloadBuilder<Pool, Client, Attributes = A::Attributes, BuiltPayload = A::BuiltPayload>,
+{
+    type Attributes = A::Attributes;
+    type BuiltPayload = A::BuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        let attempt = self.attempts.fetch_add(1, Ordering::Relaxed);
+        if attempt % 2 == 0 {
+            self.first.try_build(args)
+        } else {
+            self.second.try_build(args)
+        }
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.attempts.store(0, Ordering::Relaxed);
+        self.first.reset(payload_id);
+        self.second.reset(payload_id)
+    }
+
+    fn on_job_start(&self, config: &PayloadConfig<Self::Attributes>) {
+        self.first.on_job_start(config);
+        self.second.on_job_start(config)
+    }
+
+    fn on_better_payload(&self, payload: &Self::BuiltPayload) {
+        self.first.on_better_payload(payload);
+        self.second.on_better_payload(payload)
+    }
+
+    fn on_missing_payload(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Option<Self::BuiltPayload> {
+        self.first.on_missing_payload(args)
+    }
+
+    fn access_list(&self, config: &PayloadConfig<Self::Attributes>) -> Option<AccessList> {
+        self.first.access_list(config)
+    }
+
+    fn supported_forks(&self) -> Option<&[Hardfork]> {
+        self.first.supported_forks()
+    }
+
+    fn pool_fingerprint(&self, pool: &Pool) -> Option<u64> {
+        self.first.pool_fingerprint(pool)
+    }
+
+    fn gas_limit(&self, config: &PayloadConfig<Self::Attributes>) -> Option<u64> {
+        self.first.gas_limit(config)
+    }
+
+    fn build_empty_payload(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        A::build_empty_payload(client, config)
+    }
+
+    fn build_empty_payload_with_cached_reads(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+        cached_reads: &mut CachedReads,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        A::build_empty_payload_with_cached_reads(client, config, cached_reads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{mock_attributes, mock_generator, run_build, MockPayloadBuilder},
+        BasicPayloadJobGeneratorConfig,
+    };
+    use reth_payload_builder::PayloadJobGenerator;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn alternates_job_builds() {
+        let interval = Duration::from_secs(1);
+        let first = MockPayloadBuilder::default();
+        let second = MockPayloadBuilder::default();
+        let (generator, parent) = mock_generator(
+            AlternatingBuilder::new(first.clone(), second.clone()),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        let mut builds = Vec::new();
+        for _ in 0..4 {
+            run_build(&mut job).await;
+            builds.push((first.builds(), second.builds()));
+            tokio::time::advance(interval).await;
+        }
+        assert_eq!(builds, vec![(1, 0), (1, 1), (2, 1), (2, 2)]);
+
+        // a new job starts with the first builder again
+        drop(job);
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        run_build(&mut job).await;
+        assert_eq!((first.builds(), second.builds()), (3, 2));
+    }
+}
@@ -0,0 +1,85 @@
+//! A [PayloadBuilder] decorator that limits the number of pool transactions of a payload.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder};
+use reth_payload_builder::{error::PayloadBuilderError, PayloadId};
+
+/// A [PayloadBuilder] that limits the inner builder to at most a fixed number of pool transactions
+/// per payload, independent of gas.
+///
+/// This bounds the memory used for building, for example on nodes where block building isn't the
+/// priority. See [PayloadConfig::max_transactions](crate::PayloadConfig::max_transactions).
+#[derive(Debug, Clone)]
+pub struct BoundedTxBuilder<B> {
+    /// The builder that builds the payloads.
+    inner: B,
+    /// The maximum number of pool transactions of a payload.
+    max_transactions: usize,
+}
+
+impl<B> BoundedTxBuilder<B> {
+    /// Creates a new [BoundedTxBuilder] that includes at most `max_transactions` pool transactions
+    /// in the payloads of the inner builder.
+    pub fn new(inner: B, max_transactions: usize) -> Self {
+        Self { inner, max_transactions }
+    }
+
+    /// Returns the inner builder.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<Pool, Client, B> PayloadBuilder<Pool, Client> for BoundedTxBuilder<B>
+where
+    B: PayloadBuilder<Pool, Client>,
+{
+    type Attributes = B::Attributes;
+    type BuiltPayload = B::BuiltPayload;
+
+    fn try_build(
+        &self,
+        mut args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        let max_transactions = args
+            .config
+            .max_transactions
+            .map_or(self.max_transactions, |max| max.min(self.max_transactions));
+        args.config.max_transactions = Some(max_transactions);
+        self.inner.try_build(args)
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.inner.reset(payload_id)
+    }
+
+    forward_payload_builder_hooks!(Pool, Client, inner: B);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simple::tests::{add_transaction, build_args, mock_config},
+        SimplePayloadBuilder,
+    };
+    use reth_node_api::BuiltPayload;
+    use reth_transaction_pool::test_utils::{testing_pool, MockTransaction};
+
+    #[tokio::test]
+    async fn limits_transactions() {
+        let (client, config) = mock_config();
+        let pool = testing_pool();
+        for _ in 0..3 {
+            add_transaction(&client, &pool, MockTransaction::eip1559().with_gas_limit(21_000))
+                .await;
+        }
+
+        let BuildOutcome::Better { payload, .. } = BoundedTxBuilder::new(SimplePayloadBuilder, 2)
+            .try_build(build_args(client, pool, config))
+            .unwrap()
+        else {
+            panic!("expected a better payload")
+        };
+        assert_eq!(payload.block().body.len(), 2);
+    }
+}
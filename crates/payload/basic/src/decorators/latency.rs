@@ -0,0 +1,79 @@
+//! A [PayloadBuilder] decorator that delays every build.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder};
+use reth_payload_builder::{error::PayloadBuilderError, PayloadId};
+use std::time::Duration;
+
+/// A [PayloadBuilder] that sleeps for a fixed duration before delegating to the inner builder.
+///
+/// This simulates slow builds, for example to test the deadline and interval behavior of a job.
+/// Builds run on the blocking pool, so the delay blocks the build task and not the job.
+#[derive(Debug, Clone)]
+pub struct LatencyBuilder<B> {
+    /// The builder that builds the payloads.
+    inner: B,
+    /// How long every build is delayed.
+    latency: Duration,
+}
+
+impl<B> LatencyBuilder<B> {
+    /// Creates a new [LatencyBuilder] that delays every build of the inner builder by `latency`.
+    pub fn new(inner: B, latency: Duration) -> Self {
+        Self { inner, latency }
+    }
+
+    /// Returns the inner builder.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns the delay of every build.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
+
+impl<Pool, Client, B> PayloadBuilder<Pool, Client> for LatencyBuilder<B>
+where
+    B: PayloadBuilder<Pool, Client>,
+{
+    type Attributes = B::Attributes;
+    type BuiltPayload = B::BuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        std::thread::sleep(self.latency);
+        self.inner.try_build(args)
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.inner.reset(payload_id)
+    }
+
+    forward_payload_builder_hooks!(Pool, Client, inner: B);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simple::tests::{build_args, mock_config},
+        SimplePayloadBuilder,
+    };
+    use reth_transaction_pool::noop::NoopTransactionPool;
+
+    #[test]
+    fn delays_build() {
+        let latency = Duration::from_millis(50);
+        let builder = LatencyBuilder::new(SimplePayloadBuilder, latency);
+        let (client, config) = mock_config();
+        let args = build_args(client, NoopTransactionPool::default(), config);
+
+        let start = std::time::Instant::now();
+        let outcome = builder.try_build(args).unwrap();
+        assert!(start.elapsed() >= latency);
+        assert!(matches!(outcome, BuildOutcome::Empty { .. }));
+    }
+}
@@ -0,0 +1,87 @@
+//! A [PayloadBuilder] decorator that aborts payloads below a minimum gas used.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder};
+use reth_node_api::BuiltPayload;
+use reth_payload_builder::{error::PayloadBuilderError, PayloadId};
+use tracing::trace;
+
+/// A [PayloadBuilder] that aborts all payloads of the inner builder that use less than a minimum
+/// amount of gas.
+///
+/// This keeps the job building until a full enough payload is found or the deadline is reached.
+/// [BuildOutcome::Empty] payloads are kept, since there are no transactions to fill the block with.
+#[derive(Debug, Clone)]
+pub struct MinGasBuilder<B> {
+    /// The builder that builds the payloads.
+    inner: B,
+    /// The minimum gas used of a payload.
+    min_gas_used: u64,
+}
+
+impl<B> MinGasBuilder<B> {
+    /// Creates a new [MinGasBuilder] that only accepts payloads of the inner builder that use at
+    /// least `min_gas_used` gas.
+    pub fn new(inner: B, min_gas_used: u64) -> Self {
+        Self { inner, min_gas_used }
+    }
+
+    /// Returns the inner builder.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<Pool, Client, B> PayloadBuilder<Pool, Client> for MinGasBuilder<B>
+where
+    B: PayloadBuilder<Pool, Client>,
+{
+    type Attributes = B::Attributes;
+    type BuiltPayload = B::BuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        match self.inner.try_build(args)? {
+            BuildOutcome::Better { payload, cached_reads }
+                if payload.block().gas_used < self.min_gas_used =>
+            {
+                trace!(target: "payload_builder", gas_used = payload.block().gas_used, min_gas_used = self.min_gas_used, "skipped payload below minimum gas used");
+                Ok(BuildOutcome::Aborted { fees: payload.fees(), cached_reads })
+            }
+            outcome => Ok(outcome),
+        }
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.inner.reset(payload_id)
+    }
+
+    forward_payload_builder_hooks!(Pool, Client, inner: B);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simple::tests::{add_transaction, build_args, mock_config},
+        SimplePayloadBuilder,
+    };
+    use reth_transaction_pool::test_utils::{testing_pool, MockTransaction};
+
+    #[tokio::test]
+    async fn aborts_small_payloads() {
+        let (client, config) = mock_config();
+        let pool = testing_pool();
+        add_transaction(&client, &pool, MockTransaction::eip1559().with_gas_limit(21_000)).await;
+
+        let try_build = |min_gas_used| {
+            let args = build_args(client.clone(), pool.clone(), config.clone());
+            MinGasBuilder::new(SimplePayloadBuilder, min_gas_used).try_build(args).unwrap()
+        };
+
+        // the payload contains a single transfer
+        assert!(matches!(try_build(21_001), BuildOutcome::Aborted { .. }));
+        assert!(matches!(try_build(21_000), BuildOutcome::Better { .. }));
+    }
+}
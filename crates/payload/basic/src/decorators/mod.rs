@@ -0,0 +1,84 @@
+//! [PayloadBuilder](crate::PayloadBuilder) decorators that wrap another builder.
+
+/// Implements the [PayloadBuilder](crate::PayloadBuilder) hooks of a decorator by forwarding them
+/// to the wrapped builder.
+///
+/// This expands to all methods except [PayloadBuilder::try_build](crate::PayloadBuilder::try_build)
+/// and [PayloadBuilder::reset](crate::PayloadBuilder::reset), which every decorator implements
+/// itself. `$inner` is the field of the wrapped builder and `$B` its type.
+macro_rules! forward_payload_builder_hooks {
+    ($Pool:ident, $Client:ident, $inner:ident: $B:ident) => {
+        fn on_job_start(&self, config: &$crate::PayloadConfig<Self::Attributes>) {
+            self.$inner.on_job_start(config)
+        }
+
+        fn on_better_payload(&self, payload: &Self::BuiltPayload) {
+            self.$inner.on_better_payload(payload)
+        }
+
+        fn on_missing_payload(
+            &self,
+            args: $crate::BuildArguments<$Pool, $Client, Self::Attributes, Self::BuiltPayload>,
+        ) -> Option<Self::BuiltPayload> {
+            self.$inner.on_missing_payload(args)
+        }
+
+        fn access_list(
+            &self,
+            config: &$crate::PayloadConfig<Self::Attributes>,
+        ) -> Option<reth_primitives::AccessList> {
+            self.$inner.access_list(config)
+        }
+
+        fn supported_forks(&self) -> Option<&[reth_primitives::Hardfork]> {
+            self.$inner.supported_forks()
+        }
+
+        fn pool_fingerprint(&self, pool: &$Pool) -> Option<u64> {
+            self.$inner.pool_fingerprint(pool)
+        }
+
+        fn gas_limit(&self, config: &$crate::PayloadConfig<Self::Attributes>) -> Option<u64> {
+            self.$inner.gas_limit(config)
+        }
+
+        fn build_empty_payload(
+            client: &$Client,
+            config: $crate::PayloadConfig<Self::Attributes>,
+        ) -> Result<Self::BuiltPayload, reth_payload_builder::error::PayloadBuilderError> {
+            $B::build_empty_payload(client, config)
+        }
+
+        fn build_empty_payload_with_cached_reads(
+            client: &$Client,
+            config: $crate::PayloadConfig<Self::Attributes>,
+            cached_reads: &mut reth_payload_builder::database::CachedReads,
+        ) -> Result<Self::BuiltPayload, reth_payload_builder::error::PayloadBuilderError> {
+            $B::build_empty_payload_with_cached_reads(client, config, cached_reads)
+        }
+    };
+}
+
+mod alternating;
+pub use alternating::AlternatingBuilder;
+
+mod bounded;
+pub use bounded::BoundedTxBuilder;
+
+mod min_gas;
+pub use min_gas::MinGasBuilder;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod latency;
+#[cfg(any(test, feature = "test-utils"))]
+pub use latency::LatencyBuilder;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod tee;
+#[cfg(any(test, feature = "test-utils"))]
+pub use tee::TeeBuilder;
+
+#[cfg(any(test, feature = "tx-tracing"))]
+mod traced;
+#[cfg(any(test, feature = "tx-tracing"))]
+pub use traced::{TraceRequestHandle, TracingBuilder};
@@ -0,0 +1,133 @@
+//! A [PayloadBuilder] decorator that writes every better payload to a directory.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder};
+use reth_node_api::BuiltPayload;
+use reth_payload_builder::{error::PayloadBuilderError, PayloadId};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+use tracing::warn;
+
+/// A [PayloadBuilder] that writes the RLP encoded sealed block of every better payload of the
+/// inner builder to a directory.
+///
+/// The files are named `{payload_id}-{attempt}.rlp`, where `attempt` counts the build attempts of
+/// the job starting at zero. This allows diffing the payloads of two builder versions that were
+/// built from identical inputs. Failing to write a file doesn't fail the build.
+///
+/// The attempts are counted per job and shared by all clones, because the job clones its builder
+/// for every build.
+#[derive(Debug, Clone)]
+pub struct TeeBuilder<B> {
+    /// The builder that builds the payloads.
+    inner: B,
+    /// The directory the payloads are written to.
+    dir: PathBuf,
+    /// The number of build attempts of every job.
+    attempts: Arc<Mutex<HashMap<PayloadId, usize>>>,
+}
+
+impl<B> TeeBuilder<B> {
+    /// Creates a new [TeeBuilder] that writes the better payloads of the inner builder to `dir`.
+    pub fn new(inner: B, dir: impl Into<PathBuf>) -> Self {
+        Self { inner, dir: dir.into(), attempts: Default::default() }
+    }
+
+    /// Returns the inner builder.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns the directory the payloads are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the payload of the given job and attempt.
+    pub fn payload_path(&self, payload_id: PayloadId, attempt: usize) -> PathBuf {
+        self.dir.join(format!("{payload_id}-{attempt}.rlp"))
+    }
+}
+
+impl<Pool, Client, B> PayloadBuilder<Pool, Client> for TeeBuilder<B>
+where
+    B: PayloadBuilder<Pool, Client>,
+{
+    type Attributes = B::Attributes;
+    type BuiltPayload = B::BuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        let payload_id = args.config.payload_id();
+        let attempt = {
+            let mut attempts = self.attempts.lock().unwrap_or_else(PoisonError::into_inner);
+            let attempts = attempts.entry(payload_id).or_default();
+            *attempts += 1;
+            *attempts - 1
+        };
+        let outcome = self.inner.try_build(args)?;
+        if let BuildOutcome::Better { payload, .. } | BuildOutcome::Empty { payload, .. } = &outcome
+        {
+            let path = self.payload_path(payload_id, attempt);
+            if let Err(err) = std::fs::write(&path, alloy_rlp::encode(payload.block())) {
+                warn!(target: "payload_builder", %err, path = %path.display(), "failed to write payload");
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.attempts.lock().unwrap_or_else(PoisonError::into_inner).insert(payload_id, 0);
+        self.inner.reset(payload_id)
+    }
+
+    forward_payload_builder_hooks!(Pool, Client, inner: B);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{mock_attributes, mock_generator, run_build, MockOutcome, MockPayloadBuilder},
+        BasicPayloadJobGeneratorConfig,
+    };
+    use reth_node_api::PayloadBuilderAttributes;
+    use reth_payload_builder::{PayloadJob, PayloadJobGenerator};
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn writes_better_payloads() {
+        let dir = std::env::temp_dir().join(format!("tee-builder-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let interval = Duration::from_secs(1);
+        let inner = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Better(1),
+            MockOutcome::Aborted,
+            MockOutcome::Better(2),
+        ]);
+        let builder = TeeBuilder::new(inner, &dir);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let attributes = mock_attributes(parent.hash());
+        let payload_id = attributes.payload_id();
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        for _ in 0..3 {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        let written: Vec<_> =
+            (0..3).filter(|attempt| builder.payload_path(payload_id, *attempt).exists()).collect();
+        let best = std::fs::read(builder.payload_path(payload_id, 0)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(written, vec![0, 2]);
+        assert_eq!(best, alloy_rlp::encode(job.best_payload().unwrap().block()));
+    }
+}
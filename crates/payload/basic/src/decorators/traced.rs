@@ -0,0 +1,137 @@
+//! A [PayloadBuilder] decorator that traces the transactions of requested builds.
+
+use crate::{BuildArguments, BuildOutcome, PayloadBuilder, TxTraceHook};
+use reth_payload_builder::{error::PayloadBuilderError, PayloadId};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// A handle to request traces from a [TracingBuilder].
+///
+/// The handle is shared by all clones of the builder, so it can be obtained before the builder is
+/// moved into a generator.
+#[derive(Debug, Clone, Default)]
+pub struct TraceRequestHandle(Arc<Mutex<HashSet<PayloadId>>>);
+
+impl TraceRequestHandle {
+    /// Requests that the transactions of the next build attempt of the given job are traced.
+    pub fn request_trace(&self, payload_id: PayloadId) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).insert(payload_id);
+    }
+
+    /// Takes the pending trace request of the given job, returns whether there was one.
+    fn take(&self, payload_id: PayloadId) -> bool {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).remove(&payload_id)
+    }
+}
+
+/// A [PayloadBuilder] that traces the transactions of the builds that request it.
+///
+/// For builds of jobs with a pending request, see [TraceRequestHandle::request_trace], the inner
+/// builder is invoked with the [crate::PayloadConfig::debug_tx_traces] callback, which receives
+/// the call traces of every executed transaction.
+#[derive(Debug, Clone)]
+pub struct TracingBuilder<B> {
+    /// The builder that builds the payloads.
+    inner: B,
+    /// Receives the traces of the transactions.
+    sink: TxTraceHook,
+    /// The jobs whose next build is traced.
+    requests: TraceRequestHandle,
+}
+
+impl<B> TracingBuilder<B> {
+    /// Creates a new [TracingBuilder] that sends the transaction traces of the inner builder to
+    /// the given sink.
+    pub fn new(inner: B, sink: TxTraceHook) -> Self {
+        Self { inner, sink, requests: Default::default() }
+    }
+
+    /// Returns the inner builder.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Returns a handle to request traces of the builds of this builder.
+    pub fn trace_handle(&self) -> TraceRequestHandle {
+        self.requests.clone()
+    }
+}
+
+impl<Pool, Client, B> PayloadBuilder<Pool, Client> for TracingBuilder<B>
+where
+    B: PayloadBuilder<Pool, Client>,
+{
+    type Attributes = B::Attributes;
+    type BuiltPayload = B::BuiltPayload;
+
+    fn try_build(
+        &self,
+        mut args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        if self.requests.take(args.config.payload_id()) {
+            args.config.debug_tx_traces = Some(self.sink.clone());
+        }
+        self.inner.try_build(args)
+    }
+
+    fn reset(&self, payload_id: PayloadId) {
+        self.inner.reset(payload_id)
+    }
+
+    forward_payload_builder_hooks!(Pool, Client, inner: B);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simple::tests::{add_transaction, mock_config},
+        tests::run_build,
+        BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig, SimplePayloadBuilder,
+    };
+    use reth_payload_builder::PayloadJobGenerator;
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::test_utils::{testing_pool, MockTransaction};
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn traces_requested_builds() {
+        let (client, config) = mock_config();
+        let pool = testing_pool();
+        let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+        add_transaction(&client, &pool, tx.clone()).await;
+
+        let traced = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let traced = traced.clone();
+            TxTraceHook::new(move |hash, _| traced.lock().unwrap().push(hash))
+        };
+        let builder = TracingBuilder::new(SimplePayloadBuilder, sink);
+        let traces = builder.trace_handle();
+        let interval = Duration::from_secs(1);
+        let generator = BasicPayloadJobGenerator::with_builder(
+            client,
+            pool,
+            TokioTaskExecutor::default(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+            config.chain_spec.clone(),
+            builder,
+        );
+        let mut job = generator.new_payload_job(config.attributes.clone()).unwrap();
+
+        run_build(&mut job).await;
+        assert!(traced.lock().unwrap().is_empty());
+
+        traces.request_trace(config.payload_id());
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(*traced.lock().unwrap(), vec![tx.get_hash()]);
+
+        // the request only applies to a single build
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(traced.lock().unwrap().len(), 1);
+    }
+}
@@ -8,45 +8,87 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
-use crate::metrics::PayloadBuilderMetrics;
+use crate::metrics::{JobStats, PayloadBuilderMetrics};
+use alloy_rlp::Encodable;
 use futures_core::ready;
-use futures_util::FutureExt;
-use reth_interfaces::RethResult;
+use futures_util::{task::AtomicWaker, FutureExt};
 use reth_node_api::{BuiltPayload, PayloadBuilderAttributes};
 use reth_payload_builder::{
-    database::CachedReads, error::PayloadBuilderError, KeepPayloadJobAlive, PayloadId, PayloadJob,
-    PayloadJobGenerator,
+    database::{CachedReads, CachedReadsDBRef},
+    error::PayloadBuilderError,
+    KeepPayloadJobAlive, PayloadId, PayloadJob, PayloadJobGenerator,
 };
 use reth_primitives::{
-    constants::{EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT, RETH_CLIENT_VERSION, SLOT_DURATION},
-    proofs, BlockNumberOrTag, Bytes, ChainSpec, SealedBlock, Withdrawals, B256, U256,
+    constants::{
+        EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT, MAXIMUM_EXTRA_DATA_SIZE, MINIMUM_GAS_LIMIT,
+        RETH_CLIENT_VERSION, SLOT_DURATION,
+    },
+    eip4844::calculate_excess_blob_gas,
+    proofs, AccessList, Address, BlockNumberOrTag, Bytes, ChainSpec, Hardfork, Header, SealedBlock,
+    TransactionSigned, TransactionSignedEcRecovered, TxHash, Withdrawals, B256, U256,
 };
 use reth_provider::{
-    BlockReaderIdExt, BlockSource, CanonStateNotification, ProviderError, StateProviderFactory,
+    BlockReaderIdExt, BlockSource, BundleStateWithReceipts, CanonStateNotification, ProviderError,
+    StateProviderBox, StateProviderFactory,
 };
 use reth_revm::state_change::{
     apply_beacon_root_contract_call, post_block_withdrawals_balance_increments,
 };
+use reth_rpc_types::engine::ExecutionPayload;
+use reth_rpc_types_compat::engine::payload::{
+    block_to_payload_v3, try_block_to_payload_v1, try_block_to_payload_v2,
+};
 use reth_tasks::TaskSpawner;
 use reth_transaction_pool::TransactionPool;
 use revm::{
-    primitives::{BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg},
+    db::{states::bundle_state::BundleRetention, DatabaseRef, WrapDatabaseRef},
+    primitives::{BlockEnv, CfgEnvWithHandlerCfg, EVMError, EnvWithHandlerCfg, ResultAndState},
     Database, DatabaseCommit, Evm, State,
 };
 use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
     future::Future,
+    marker::PhantomData,
     pin::Pin,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize},
+        Arc,
+    },
     task::{Context, Poll},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
-    sync::{oneshot, Semaphore},
+    sync::{broadcast, oneshot, Semaphore, SemaphorePermit},
     time::{Interval, Sleep},
 };
 use tracing::{debug, trace, warn};
 
 mod metrics;
+pub use metrics::JobMetrics;
+
+mod multi;
+pub use multi::{MultiBuilderGenerator, MultiBuilderJob, ResolveBestOfPayloads};
+
+mod decorators;
+pub use decorators::{AlternatingBuilder, BoundedTxBuilder, MinGasBuilder};
+#[cfg(any(test, feature = "test-utils"))]
+pub use decorators::{LatencyBuilder, TeeBuilder};
+#[cfg(any(test, feature = "tx-tracing"))]
+pub use decorators::{TraceRequestHandle, TracingBuilder};
+
+#[cfg(any(test, feature = "test-utils"))]
+mod simple;
+#[cfg(any(test, feature = "test-utils"))]
+pub use simple::SimplePayloadBuilder;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod ordered;
+#[cfg(any(test, feature = "test-utils"))]
+pub use ordered::OrderedPayloadBuilder;
+
+mod tx_tracing;
+pub use tx_tracing::TxTraceHook;
 
 /// The [`PayloadJobGenerator`] that creates [`BasicPayloadJob`]s.
 #[derive(Debug)]
@@ -69,12 +111,31 @@ pub struct BasicPayloadJobGenerator<Client, Pool, Tasks, Builder> {
     builder: Builder,
     /// Stored cached_reads for new payload jobs.
     pre_cached: Option<PrecachedState>,
+    /// The controls of the jobs of this generator that haven't been dropped yet.
+    ///
+    /// See [PayloadJobsHandle]
+    job_controls: PayloadJobControls,
+    /// The source of the current time for job deadlines.
+    clock: Arc<dyn PayloadClock>,
+    /// The [SharedJobState] of this generator's jobs.
+    ///
+    /// See [JobState]
+    shared: Arc<dyn JobState<Pool, Client, Builder>>,
+    /// Rejects new jobs after too many jobs failed in a row.
+    circuit_breaker: PayloadCircuitBreaker,
+    /// Whether the generator was shut down.
+    ///
+    /// See [BasicPayloadJobGenerator::shutdown]
+    shutdown: PayloadJobShutdownHandle,
 }
 
 // === impl BasicPayloadJobGenerator ===
 
 impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks, Builder> {
     /// Creates a new [BasicPayloadJobGenerator] with the given config and custom [PayloadBuilder]
+    ///
+    /// Logs a warning for every upcoming fork of the chain that the builder doesn't support, see
+    /// [PayloadBuilder::supported_forks].
     pub fn with_builder(
         client: Client,
         pool: Pool,
@@ -82,19 +143,47 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
         config: BasicPayloadJobGeneratorConfig,
         chain_spec: Arc<ChainSpec>,
         builder: Builder,
-    ) -> Self {
+    ) -> Self
+    where
+        Client: 'static,
+        Pool: 'static,
+        Builder: PayloadBuilder<Pool, Client> + 'static,
+    {
+        if let Some(supported) = builder.supported_forks() {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            for fork in unsupported_upcoming_forks(&chain_spec, supported, now) {
+                warn!(target: "payload_builder", ?fork, "payload builder doesn't support upcoming fork");
+            }
+        }
+
         Self {
             client,
             pool,
             executor,
             payload_task_guard: PayloadTaskGuard::new(config.max_payload_tasks),
+            circuit_breaker: PayloadCircuitBreaker::new(
+                config.max_failed_jobs,
+                config.failed_jobs_cooldown,
+            ),
             config,
             chain_spec,
             builder,
             pre_cached: None,
+            job_controls: Default::default(),
+            shutdown: Default::default(),
+            clock: Arc::new(SystemClock),
+            shared: Arc::new(SharedJobState::default()),
         }
     }
 
+    /// Sets the source of the current time that job deadlines are computed with.
+    ///
+    /// Defaults to [SystemClock].
+    pub fn with_clock(mut self, clock: impl PayloadClock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
     /// Returns the maximum duration a job should be allowed to run.
     ///
     /// This adheres to the following specification:
@@ -104,7 +193,7 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
     // See also <https://github.com/ethereum/execution-apis/blob/431cf72fd3403d946ca3e3afc36b973fc87e0e89/src/engine/paris.md?plain=1#L137>
     #[inline]
     fn max_job_duration(&self, unix_timestamp: u64) -> Duration {
-        let duration_until_timestamp = duration_until(unix_timestamp);
+        let duration_until_timestamp = duration_until(self.clock.now(), unix_timestamp);
 
         // safety in case clocks are bad
         let duration_until_timestamp = duration_until_timestamp.min(self.config.deadline * 3);
@@ -114,9 +203,19 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
 
     /// Returns the [Instant](tokio::time::Instant) at which the job should be terminated because it
     /// is considered timed out.
+    ///
+    /// The job duration is bounded by [BasicPayloadJobGeneratorConfig::max_deadline], if set.
     #[inline]
     fn job_deadline(&self, unix_timestamp: u64) -> tokio::time::Instant {
-        tokio::time::Instant::now() + self.max_job_duration(unix_timestamp)
+        let mut duration = self.max_job_duration(unix_timestamp);
+        if let Some(max_deadline) = self.config.max_deadline {
+            if duration > max_deadline {
+                warn!(target: "payload_builder", unix_timestamp, requested = ?duration, bounded = ?max_deadline, "payload timestamp too far in the future, bounding the job deadline");
+                duration = max_deadline;
+            }
+        }
+
+        tokio::time::Instant::now() + duration
     }
 
     /// Returns a reference to the tasks type
@@ -124,6 +223,57 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
         &self.executor
     }
 
+    /// Returns a handle that can control the active jobs of this generator.
+    ///
+    /// The handle can still be used once the generator was moved into the payload builder
+    /// service.
+    pub fn jobs_handle(&self) -> PayloadJobsHandle {
+        PayloadJobsHandle(self.job_controls.clone())
+    }
+
+    /// Returns a handle that can pause and resume building of the active job with the given id.
+    ///
+    /// See [PayloadJobsHandle::pause_handle].
+    pub fn pause_handle(&self, payload_id: PayloadId) -> Option<PayloadJobPauseHandle> {
+        self.jobs_handle().pause_handle(payload_id)
+    }
+
+    /// Returns a handle that can shut down the generator and its jobs.
+    ///
+    /// Unlike [BasicPayloadJobGenerator::shutdown], the handle can still be used once the
+    /// generator was moved into the payload builder service.
+    pub fn shutdown_handle(&self) -> PayloadJobShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Shuts down the generator.
+    ///
+    /// See [PayloadJobShutdownHandle::shutdown].
+    pub fn shutdown(&self) {
+        self.shutdown.shutdown()
+    }
+
+    /// Returns a handle that can change the maximum number of tasks to spawn for building
+    /// payloads.
+    ///
+    /// Unlike [BasicPayloadJobGenerator::set_max_payload_tasks], the handle can still be used once
+    /// the generator was moved into the payload builder service.
+    pub fn payload_tasks_handle(&self) -> PayloadTasksHandle {
+        PayloadTasksHandle(self.payload_task_guard.clone())
+    }
+
+    /// Sets the maximum number of tasks to spawn for building payloads.
+    ///
+    /// See [PayloadTasksHandle::set_max_payload_tasks].
+    pub fn set_max_payload_tasks(&self, max_payload_tasks: usize) {
+        self.payload_tasks_handle().set_max_payload_tasks(max_payload_tasks)
+    }
+
+    /// Returns true if the generator was shut down.
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.is_shutdown()
+    }
+
     /// Returns the pre-cached reads for the given parent block if it matches the cached state's
     /// block.
     fn maybe_pre_cached(&self, parent: B256) -> Option<CachedReads> {
@@ -131,6 +281,18 @@ impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks,
     }
 }
 
+impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks, Builder>
+where
+    Client: 'static,
+    Pool: 'static,
+    Builder: PayloadBuilder<Pool, Client> + 'static,
+{
+    /// Returns the state shared with the jobs of this generator.
+    fn shared(&self) -> &SharedJobState<Pool, Client, Builder> {
+        self.shared.state()
+    }
+}
+
 // === impl BasicPayloadJobGenerator ===
 
 impl<Client, Pool, Tasks, Builder> PayloadJobGenerator
@@ -149,11 +311,20 @@ where
         &self,
         attributes: <Self::Job as PayloadJob>::PayloadAttributes,
     ) -> Result<Self::Job, PayloadBuilderError> {
+        if self.is_shutdown() {
+            return Err(PayloadBuilderError::Shutdown)
+        }
+
+        if let Some(failed_jobs) = self.circuit_breaker.open_failed_jobs() {
+            debug!(target: "payload_builder", failed_jobs, "rejecting payload job after repeated job failures");
+            return Err(PayloadBuilderError::BuildingSuspended { failed_jobs })
+        }
+
         let parent_block = if attributes.parent().is_zero() {
             // use latest block if parent is zero: genesis block
             self.client
                 .block_by_number_or_tag(BlockNumberOrTag::Latest)?
-                .ok_or_else(|| PayloadBuilderError::MissingParentBlock(attributes.parent()))?
+                .ok_or(PayloadBuilderError::MissingLatestBlock)?
                 .seal_slow()
         } else {
             let block = self
@@ -165,19 +336,36 @@ where
             block.seal(attributes.parent())
         };
 
-        let config = PayloadConfig::new(
+        let mut config = PayloadConfig::new(
             Arc::new(parent_block),
-            self.config.extradata.clone(),
+            self.config.job_extradata(),
             attributes,
             Arc::clone(&self.chain_spec),
         );
+        if let Some(gas_limit) = self.builder.gas_limit(&config) {
+            config = config.with_gas_limit(gas_limit);
+        }
+        config.debug_bundle_state = self.config.debug_bundle_state.clone();
+        config.latest_state_fallback = self.config.latest_state_fallback;
+
+        let min_gas_limit = U256::from(self.config.min_gas_limit);
+        if config.initialized_block_env.gas_limit < min_gas_limit {
+            warn!(target: "payload_builder", gas_limit = %config.initialized_block_env.gas_limit, %min_gas_limit, "block gas limit below the minimum, using the minimum");
+            config.initialized_block_env.gas_limit = min_gas_limit;
+        }
 
         let until = self.job_deadline(config.attributes.timestamp());
         let deadline = Box::pin(tokio::time::sleep_until(until));
 
         let cached_reads = self.maybe_pre_cached(config.parent_block.hash());
+        let builder = self.builder.clone();
+        builder.reset(config.payload_id());
+        let access_list = builder.access_list(&config);
 
-        Ok(BasicPayloadJob {
+        let active = self.shared().active_jobs.register(&config);
+        let control = self.job_controls.register(config.payload_id());
+
+        let mut job = BasicPayloadJob {
             config,
             client: self.client.clone(),
             pool: self.pool.clone(),
@@ -189,8 +377,46 @@ where
             cached_reads,
             payload_task_guard: self.payload_task_guard.clone(),
             metrics: Default::default(),
-            builder: self.builder.clone(),
-        })
+            builder,
+            control,
+            shutdown: self.shutdown.clone(),
+            shutdown_waker: self.shutdown.register(),
+            access_list,
+            candidates_per_build: self.config.candidates_per_build,
+            stats: Default::default(),
+            started: false,
+            resolve_wait_for_pending: self.config.resolve_wait_for_pending,
+            build_timeout: self.config.build_timeout,
+            max_stale_intervals: self.config.max_stale_intervals,
+            max_build_attempts: self.config.max_build_attempts,
+            build_attempts: 0,
+            better_payloads: None,
+            stale_intervals: 0,
+            empty_payloads: self.shared().empty_payloads.clone(),
+            checks: PayloadChecks {
+                parent_hash: config.parent_block.hash(),
+                max_encoded_block_size: self.config.max_encoded_block_size,
+                ranking: Arc::clone(&self.config.ranking),
+            },
+            max_cached_reads_bytes: self.config.max_cached_reads_bytes,
+            active,
+            fatal_failures: None,
+            circuit_breaker: self.circuit_breaker.clone(),
+            last_spawn_instant: None,
+            created_at: tokio::time::Instant::now(),
+            first_payload_recorded: false,
+            build_once: self.config.build_once,
+            last_fingerprint: None,
+        };
+
+        if self.config.eager_first_build {
+            trace!(target: "payload_builder", id=%job.config.payload_id(), "spawn first payload build task on job creation");
+            job.ensure_started();
+            job.build_attempts += 1;
+            job.pending_block = Some(job.spawn_build());
+        }
+
+        Ok(job)
     }
 
     fn on_new_state(&mut self, new_state: CanonStateNotification) {
@@ -214,6 +440,56 @@ where
     }
 }
 
+impl<Client, Pool, Tasks, Builder> BasicPayloadJobGenerator<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + 'static,
+    Pool: 'static,
+    Tasks: TaskSpawner,
+    Builder: PayloadBuilder<Pool, Client> + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Clone,
+{
+    /// Resolves the active job with the given id without access to the job itself.
+    ///
+    /// This resolves the best payload of the job so far, or the empty payload if the job didn't
+    /// build a payload yet. Unlike [PayloadJob::resolve], this doesn't consume the state of the
+    /// job, which keeps running until it is resolved by its owner or reaches its deadline.
+    ///
+    /// Returns `None` if there's no active job with the given id.
+    pub fn resolve(
+        &self,
+        id: PayloadId,
+    ) -> Option<(ResolveBestPayload<Builder::BuiltPayload>, KeepPayloadJobAlive)> {
+        let (config, best_payload) = self.shared().active_jobs.get(id)?;
+        let config_parent_hash = config.parent_block.hash();
+        let empty_payload = best_payload.is_none().then(|| {
+            debug!(target: "payload_builder", %id, "no best payload yet to resolve, building empty payload");
+            spawn_empty_payload::<_, Pool, _, Builder>(
+                &self.executor,
+                self.client.clone(),
+                config,
+                CachedReads::default(),
+                &self.shared().empty_payloads,
+            )
+        });
+
+        Some((
+            ResolveBestPayload {
+                best_payload,
+                maybe_better: None,
+                empty_payload,
+                wait_for_pending: None,
+                checks: PayloadChecks {
+                    parent_hash: config_parent_hash,
+                    max_encoded_block_size: self.config.max_encoded_block_size,
+                    ranking: Arc::clone(&self.config.ranking),
+                },
+            },
+            KeepPayloadJobAlive::Yes,
+        ))
+    }
+}
+
 /// Pre-filled [CachedReads] for a specific block.
 ///
 /// This is extracted from the [CanonStateNotification] for the tip block.
@@ -225,680 +501,4635 @@ pub struct PrecachedState {
     pub cached: CachedReads,
 }
 
-/// Restricts how many generator tasks can be executed at once.
+/// A handle to control the active [BasicPayloadJob]s of a [BasicPayloadJobGenerator].
+///
+/// The handle is obtained before the generator is moved into the payload builder service, see
+/// [BasicPayloadJobGenerator::jobs_handle].
 #[derive(Debug, Clone)]
-struct PayloadTaskGuard(Arc<Semaphore>);
+pub struct PayloadJobsHandle(PayloadJobControls);
 
-// === impl PayloadTaskGuard ===
+// === impl PayloadJobsHandle ===
 
-impl PayloadTaskGuard {
-    fn new(max_payload_tasks: usize) -> Self {
-        Self(Arc::new(Semaphore::new(max_payload_tasks)))
+impl PayloadJobsHandle {
+    /// Returns a handle that can pause and resume building of the active job with the given id.
+    ///
+    /// Returns `None` if there's no active job with the given id.
+    pub fn pause_handle(&self, payload_id: PayloadId) -> Option<PayloadJobPauseHandle> {
+        let control = self.0.get(payload_id)?;
+        Some(PayloadJobPauseHandle(Arc::clone(&control.paused)))
     }
-}
 
-/// Settings for the [BasicPayloadJobGenerator].
-#[derive(Debug, Clone)]
-pub struct BasicPayloadJobGeneratorConfig {
-    /// Data to include in the block's extra data field.
-    extradata: Bytes,
-    /// Target gas ceiling for built blocks, defaults to [ETHEREUM_BLOCK_GAS_LIMIT] gas.
-    max_gas_limit: u64,
-    /// The interval at which the job should build a new payload after the last.
-    interval: Duration,
-    /// The deadline for when the payload builder job should resolve.
+    /// Marks the next build attempt of the active job with the given id as a dry run.
     ///
-    /// By default this is [SLOT_DURATION]: 12s
-    deadline: Duration,
-    /// Maximum number of tasks to spawn for building a payload.
-    max_payload_tasks: usize,
+    /// The outcome of a dry run is only logged, neither its cached reads nor its payload are
+    /// committed to the job.
+    ///
+    /// Returns false if there's no active job with the given id.
+    pub fn request_dry_run(&self, payload_id: PayloadId) -> bool {
+        let Some(control) = self.0.get(payload_id) else { return false };
+        control.dry_run_requested.store(true, std::sync::atomic::Ordering::Relaxed);
+        true
+    }
+
+    /// Forces the given transactions at the top of the block of all following build attempts of
+    /// the active job with the given id, see [PayloadConfig::forced_top_of_block].
+    ///
+    /// Returns false if there's no active job with the given id.
+    pub fn force_top_of_block(
+        &self,
+        payload_id: PayloadId,
+        transactions: Vec<TransactionSigned>,
+    ) -> bool {
+        let Some(control) = self.0.get(payload_id) else { return false };
+        *control.forced_top_of_block.lock().unwrap_or_else(std::sync::PoisonError::into_inner) =
+            Some(transactions);
+        true
+    }
 }
 
-// === impl BasicPayloadJobGeneratorConfig ===
+/// The state of a [BasicPayloadJob] that can be changed through a [PayloadJobsHandle].
+#[derive(Debug, Default)]
+struct PayloadJobControl {
+    /// Whether spawning new build tasks is currently paused, see [PayloadJobPauseHandle].
+    paused: Arc<AtomicBool>,
+    /// Whether the next build attempt is a dry run, see [PayloadJobsHandle::request_dry_run].
+    dry_run_requested: AtomicBool,
+    /// Transactions to force at the top of the block, see [PayloadJobsHandle::force_top_of_block].
+    forced_top_of_block: std::sync::Mutex<Option<Vec<TransactionSigned>>>,
+}
 
-impl BasicPayloadJobGeneratorConfig {
-    /// Sets the interval at which the job should build a new payload after the last.
-    pub fn interval(mut self, interval: Duration) -> Self {
-        self.interval = interval;
-        self
+impl PayloadJobControl {
+    /// Returns true if spawning new build tasks is currently paused.
+    fn is_paused(&self) -> bool {
+        self.paused.load(std::sync::atomic::Ordering::Relaxed)
     }
 
-    /// Sets the deadline when this job should resolve.
-    pub fn deadline(mut self, deadline: Duration) -> Self {
-        self.deadline = deadline;
-        self
+    /// Returns true if the next build attempt is a dry run.
+    fn is_dry_run_requested(&self) -> bool {
+        self.dry_run_requested.load(std::sync::atomic::Ordering::Relaxed)
     }
 
-    /// Sets the maximum number of tasks to spawn for building a payload(s).
-    ///
-    /// # Panics
-    ///
-    /// If `max_payload_tasks` is 0.
-    pub fn max_payload_tasks(mut self, max_payload_tasks: usize) -> Self {
-        assert!(max_payload_tasks > 0, "max_payload_tasks must be greater than 0");
-        self.max_payload_tasks = max_payload_tasks;
-        self
+    /// Returns true if the next build attempt is a dry run and clears the request.
+    fn take_dry_run_request(&self) -> bool {
+        self.dry_run_requested.swap(false, std::sync::atomic::Ordering::Relaxed)
     }
 
-    /// Sets the data to include in the block's extra data field.
-    ///
-    /// Defaults to the current client version: `rlp(RETH_CLIENT_VERSION)`.
-    pub fn extradata(mut self, extradata: Bytes) -> Self {
-        self.extradata = extradata;
-        self
+    /// Returns true if transactions were forced at the top of the block since the last build.
+    fn has_forced_top_of_block(&self) -> bool {
+        self.forced_top_of_block.lock().unwrap_or_else(std::sync::PoisonError::into_inner).is_some()
     }
 
-    /// Sets the target gas ceiling for mined blocks.
-    ///
-    /// Defaults to [ETHEREUM_BLOCK_GAS_LIMIT] gas.
-    pub fn max_gas_limit(mut self, max_gas_limit: u64) -> Self {
-        self.max_gas_limit = max_gas_limit;
-        self
+    /// Takes the transactions that were forced at the top of the block since the last call.
+    fn take_forced_top_of_block(&self) -> Option<Vec<TransactionSigned>> {
+        self.forced_top_of_block.lock().unwrap_or_else(std::sync::PoisonError::into_inner).take()
     }
 }
 
-impl Default for BasicPayloadJobGeneratorConfig {
-    fn default() -> Self {
-        Self {
-            extradata: alloy_rlp::encode(RETH_CLIENT_VERSION.as_bytes()).into(),
-            max_gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
-            interval: Duration::from_secs(1),
-            // 12s slot time
-            deadline: SLOT_DURATION,
-            max_payload_tasks: 3,
-        }
+/// The controls of the active jobs of a generator by payload id.
+///
+/// Every job registers its control on creation and unregisters it once it is dropped, see
+/// [PayloadJobControlEntry].
+#[derive(Debug, Clone, Default)]
+struct PayloadJobControls(Arc<std::sync::Mutex<HashMap<PayloadId, Arc<PayloadJobControl>>>>);
+
+impl PayloadJobControls {
+    /// Locks the controls.
+    fn controls(&self) -> std::sync::MutexGuard<'_, HashMap<PayloadId, Arc<PayloadJobControl>>> {
+        self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Registers the control of a new job with the given id.
+    fn register(&self, id: PayloadId) -> PayloadJobControlEntry {
+        let control = Arc::new(PayloadJobControl::default());
+        self.controls().insert(id, Arc::clone(&control));
+        PayloadJobControlEntry { controls: self.clone(), id, control }
+    }
+
+    /// Returns the control of the job with the given id.
+    fn get(&self, id: PayloadId) -> Option<Arc<PayloadJobControl>> {
+        self.controls().get(&id).cloned()
     }
 }
 
-/// A basic payload job that continuously builds a payload with the best transactions from the pool.
+/// The registration of a job in [PayloadJobControls], the control is unregistered on drop.
 #[derive(Debug)]
-pub struct BasicPayloadJob<Client, Pool, Tasks, Builder>
-where
-    Builder: PayloadBuilder<Pool, Client>,
-{
-    /// The configuration for how the payload will be created.
-    config: PayloadConfig<Builder::Attributes>,
-    /// The client that can interact with the chain.
-    client: Client,
-    /// The transaction pool.
-    pool: Pool,
-    /// How to spawn building tasks
-    executor: Tasks,
-    /// The deadline when this job should resolve.
-    deadline: Pin<Box<Sleep>>,
-    /// The interval at which the job should build a new payload after the last.
-    interval: Interval,
-    /// The best payload so far.
-    best_payload: Option<Builder::BuiltPayload>,
-    /// Receiver for the block that is currently being built.
-    pending_block: Option<PendingPayload<Builder::BuiltPayload>>,
-    /// Restricts how many generator tasks can be executed at once.
-    payload_task_guard: PayloadTaskGuard,
-    /// Caches all disk reads for the state the new payloads builds on
-    ///
-    /// This is used to avoid reading the same state over and over again when new attempts are
-    /// triggered, because during the building process we'll repeatedly execute the transactions.
-    cached_reads: Option<CachedReads>,
-    /// metrics for this type
-    metrics: PayloadBuilderMetrics,
-    /// The type responsible for building payloads.
-    ///
-    /// See [PayloadBuilder]
-    builder: Builder,
+struct PayloadJobControlEntry {
+    controls: PayloadJobControls,
+    id: PayloadId,
+    control: Arc<PayloadJobControl>,
 }
 
-impl<Client, Pool, Tasks, Builder> Future for BasicPayloadJob<Client, Pool, Tasks, Builder>
-where
-    Client: StateProviderFactory + Clone + Unpin + 'static,
-    Pool: TransactionPool + Unpin + 'static,
-    Tasks: TaskSpawner + Clone + 'static,
-    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
-    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
-    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
-{
-    type Output = Result<(), PayloadBuilderError>;
+impl std::ops::Deref for PayloadJobControlEntry {
+    type Target = PayloadJobControl;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
+    fn deref(&self) -> &Self::Target {
+        &self.control
+    }
+}
 
-        // check if the deadline is reached
-        if this.deadline.as_mut().poll(cx).is_ready() {
-            trace!(target: "payload_builder", "payload building deadline reached");
-            return Poll::Ready(Ok(()))
+impl Drop for PayloadJobControlEntry {
+    fn drop(&mut self) {
+        let mut controls = self.controls.controls();
+        // a newer job with the same id replaced the control
+        if controls.get(&self.id).is_some_and(|control| Arc::ptr_eq(control, &self.control)) {
+            controls.remove(&self.id);
         }
+    }
+}
 
-        // check if the interval is reached
-        while this.interval.poll_tick(cx).is_ready() {
-            // start a new job if there is no pending block and we haven't reached the deadline
-            if this.pending_block.is_none() {
-                trace!(target: "payload_builder", "spawn new payload build task");
-                let (tx, rx) = oneshot::channel();
-                let client = this.client.clone();
-                let pool = this.pool.clone();
-                let cancel = Cancelled::default();
-                let _cancel = cancel.clone();
-                let guard = this.payload_task_guard.clone();
-                let payload_config = this.config.clone();
-                let best_payload = this.best_payload.clone();
-                this.metrics.inc_initiated_payload_builds();
-                let cached_reads = this.cached_reads.take().unwrap_or_default();
-                let builder = this.builder.clone();
-                this.executor.spawn_blocking(Box::pin(async move {
-                    // acquire the permit for executing the task
-                    let _permit = guard.0.acquire().await;
-                    let args = BuildArguments {
-                        client,
-                        pool,
-                        cached_reads,
-                        config: payload_config,
-                        cancel,
-                        best_payload,
-                    };
-                    let result = builder.try_build(args);
-                    let _ = tx.send(result);
-                }));
-
-                this.pending_block = Some(PendingPayload { _cancel, payload: rx });
-            }
-        }
+/// A handle to pause and resume the rebuild loop of a [BasicPayloadJob].
+///
+/// While paused, the job doesn't spawn new build tasks, but it keeps its in-flight build, cached
+/// reads and best payload, and still resolves at its deadline. Building resumes at the next
+/// interval after [PayloadJobPauseHandle::resume] is called.
+#[derive(Debug, Clone)]
+pub struct PayloadJobPauseHandle(Arc<AtomicBool>);
 
-        // poll the pending block
-        if let Some(mut fut) = this.pending_block.take() {
-            match fut.poll_unpin(cx) {
-                Poll::Ready(Ok(outcome)) => {
-                    this.interval.reset();
-                    match outcome {
-                        BuildOutcome::Better { payload, cached_reads } => {
-                            this.cached_reads = Some(cached_reads);
-                            debug!(target: "payload_builder", value = %payload.fees(), "built better payload");
-                            let payload = payload;
-                            this.best_payload = Some(payload);
-                        }
-                        BuildOutcome::Aborted { fees, cached_reads } => {
-                            this.cached_reads = Some(cached_reads);
-                            trace!(target: "payload_builder", worse_fees = %fees, "skipped payload build of worse block");
-                        }
-                        BuildOutcome::Cancelled => {
-                            unreachable!("the cancel signal never fired")
-                        }
-                    }
-                }
-                Poll::Ready(Err(error)) => {
-                    // job failed, but we simply try again next interval
-                    debug!(target: "payload_builder", %error, "payload build attempt failed");
-                    this.metrics.inc_failed_payload_builds();
-                }
-                Poll::Pending => {
-                    this.pending_block = Some(fut);
-                }
-            }
-        }
+// === impl PayloadJobPauseHandle ===
 
-        Poll::Pending
+impl PayloadJobPauseHandle {
+    /// Pauses building of the job.
+    pub fn pause(&self) {
+        self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Resumes building of the job.
+    pub fn resume(&self) {
+        self.0.store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Returns true if building is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
     }
 }
 
-impl<Client, Pool, Tasks, Builder> PayloadJob for BasicPayloadJob<Client, Pool, Tasks, Builder>
-where
-    Client: StateProviderFactory + Clone + Unpin + 'static,
-    Pool: TransactionPool + Unpin + 'static,
-    Tasks: TaskSpawner + Clone + 'static,
-    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
-    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
-    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
-{
-    type PayloadAttributes = Builder::Attributes;
-    type ResolvePayloadFuture = ResolveBestPayload<Self::BuiltPayload>;
-    type BuiltPayload = Builder::BuiltPayload;
+/// A handle to shut down a [BasicPayloadJobGenerator] and its [BasicPayloadJob]s.
+///
+/// The handle is obtained before the generator is moved into the payload builder service, see
+/// [BasicPayloadJobGenerator::shutdown_handle].
+#[derive(Debug, Clone, Default)]
+pub struct PayloadJobShutdownHandle(Arc<PayloadJobShutdown>);
 
-    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
-        if let Some(ref payload) = self.best_payload {
-            return Ok(payload.clone())
+/// The state shared by all [PayloadJobShutdownHandle]s of a generator.
+#[derive(Debug, Default)]
+struct PayloadJobShutdown {
+    /// Whether the generator was shut down.
+    shutdown: AtomicBool,
+    /// The wakers of the jobs of the generator that weren't dropped yet.
+    wakers: std::sync::Mutex<Vec<std::sync::Weak<AtomicWaker>>>,
+}
+
+// === impl PayloadJobShutdownHandle ===
+
+impl PayloadJobShutdownHandle {
+    /// Shuts down the generator.
+    ///
+    /// New jobs fail with [PayloadBuilderError::Shutdown]. The active jobs are woken and cancel
+    /// their in progress build. They don't build anymore, but stay alive until they're resolved or
+    /// their deadline is reached, so their best payload can still be resolved.
+    pub fn shutdown(&self) {
+        self.0.shutdown.store(true, std::sync::atomic::Ordering::Relaxed);
+        for waker in self.wakers().iter().filter_map(std::sync::Weak::upgrade) {
+            waker.wake();
         }
-        // No payload has been built yet, but we need to return something that the CL then can
-        // deliver, so we need to return an empty payload.
-        //
-        // Note: it is assumed that this is unlikely to happen, as the payload job is started right
-        // away and the first full block should have been built by the time CL is requesting the
-        // payload.
-        self.metrics.inc_requested_empty_payload();
-        Builder::build_empty_payload(&self.client, self.config.clone())
     }
 
-    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
-        Ok(self.config.attributes.clone())
+    /// Returns true if the generator was shut down.
+    pub fn is_shutdown(&self) -> bool {
+        self.0.shutdown.load(std::sync::atomic::Ordering::Relaxed)
     }
 
-    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
-        let best_payload = self.best_payload.take();
-        let maybe_better = self.pending_block.take();
-        let mut empty_payload = None;
-
-        if best_payload.is_none() {
-            debug!(target: "payload_builder", id=%self.config.payload_id(), "no best payload yet to resolve, building empty payload");
-
-            let args = BuildArguments {
-                client: self.client.clone(),
-                pool: self.pool.clone(),
-                cached_reads: self.cached_reads.take().unwrap_or_default(),
-                config: self.config.clone(),
-                cancel: Cancelled::default(),
-                best_payload: None,
-            };
-
-            // TODO: create optimism payload job, that wraps this type, that implements PayloadJob
-            // with this branch. remove this branch from the non-op code. remove
-            // `on_missing_payload` requirement from builder trait
-            if let Some(payload) = self.builder.on_missing_payload(args) {
-                debug!(target: "payload_builder", id=%self.config.payload_id(), "resolving fallback payload as best payload");
-                return (
-                    ResolveBestPayload { best_payload: Some(payload), maybe_better, empty_payload },
-                    KeepPayloadJobAlive::Yes,
-                )
-            }
+    /// Locks the wakers of the jobs.
+    fn wakers(&self) -> std::sync::MutexGuard<'_, Vec<std::sync::Weak<AtomicWaker>>> {
+        self.0.wakers.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
 
-            // if no payload has been built yet
-            self.metrics.inc_requested_empty_payload();
-            // no payload built yet, so we need to return an empty payload
-            let (tx, rx) = oneshot::channel();
-            let client = self.client.clone();
-            let config = self.config.clone();
-            self.executor.spawn_blocking(Box::pin(async move {
-                let res = Builder::build_empty_payload(&client, config);
-                let _ = tx.send(res);
-            }));
+    /// Registers a new job, whose waker is woken on shutdown.
+    fn register(&self) -> Arc<AtomicWaker> {
+        let waker = Arc::new(AtomicWaker::new());
+        let mut wakers = self.wakers();
+        wakers.retain(|waker| waker.strong_count() > 0);
+        wakers.push(Arc::downgrade(&waker));
+        waker
+    }
+}
 
-            empty_payload = Some(rx);
-        }
+/// A source of the current wall clock time.
+///
+/// This is used to determine how long a [BasicPayloadJob] may run until the payload's timestamp.
+pub trait PayloadClock: fmt::Debug + Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
 
-        let fut = ResolveBestPayload { best_payload, maybe_better, empty_payload };
+/// The [PayloadClock] that reads the system time.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct SystemClock;
 
-        (fut, KeepPayloadJobAlive::No)
+impl PayloadClock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
     }
 }
 
-/// The future that returns the best payload to be served to the consensus layer.
-///
-/// This returns the payload that's supposed to be sent to the CL.
+/// Number of consecutive identical, potentially fatal build failures after which a
+/// [BasicPayloadJob] gives up.
 ///
-/// If payload has been built so far, it will return that, but it will check if there's a better
-/// payload available from an in progress build job. If so it will return that.
+/// See [PayloadBuilderError::is_potentially_fatal].
+pub const MAX_FATAL_BUILD_FAILURES: u32 = 3;
+
+/// Counts the jobs of a generator that gave up in a row, shared by all jobs of a generator.
 ///
-/// If no payload has been built so far, it will either return an empty payload or the result of the
-/// in progress build job, whatever finishes first.
-#[derive(Debug)]
-pub struct ResolveBestPayload<Payload> {
-    /// Best payload so far.
-    best_payload: Option<Payload>,
-    /// Regular payload job that's currently running that might produce a better payload.
-    maybe_better: Option<PendingPayload<Payload>>,
-    /// The empty payload building job in progress.
-    empty_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
+/// See [BasicPayloadJobGeneratorConfig::max_failed_jobs].
+#[derive(Debug, Clone)]
+struct PayloadCircuitBreaker {
+    /// Number of failed jobs in a row after which new jobs are rejected, `None` if disabled.
+    max_failed_jobs: Option<u32>,
+    /// How long new jobs are rejected.
+    cooldown: Duration,
+    /// The shared state of the circuit breaker.
+    state: Arc<std::sync::Mutex<PayloadCircuitBreakerState>>,
 }
 
-impl<Payload> Future for ResolveBestPayload<Payload>
-where
-    Payload: Unpin,
-{
-    type Output = Result<Payload, PayloadBuilderError>;
+/// The state of a [PayloadCircuitBreaker].
+#[derive(Debug, Default)]
+struct PayloadCircuitBreakerState {
+    /// The number of jobs that failed in a row.
+    failed_jobs: u32,
+    /// Until when new jobs are rejected.
+    open_until: Option<tokio::time::Instant>,
+}
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
+impl PayloadCircuitBreaker {
+    /// Creates a new circuit breaker that rejects jobs for `cooldown` once `max_failed_jobs` jobs
+    /// failed in a row.
+    fn new(max_failed_jobs: Option<u32>, cooldown: Duration) -> Self {
+        Self { max_failed_jobs, cooldown, state: Default::default() }
+    }
 
-        // check if there is a better payload before returning the best payload
-        if let Some(fut) = Pin::new(&mut this.maybe_better).as_pin_mut() {
-            if let Poll::Ready(res) = fut.poll(cx) {
-                this.maybe_better = None;
-                if let Ok(BuildOutcome::Better { payload, .. }) = res {
-                    debug!(target: "payload_builder", "resolving better payload");
-                    return Poll::Ready(Ok(payload))
-                }
-            }
+    /// Locks the state.
+    fn state(&self) -> std::sync::MutexGuard<'_, PayloadCircuitBreakerState> {
+        self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the number of failed jobs in a row if new jobs are currently rejected.
+    fn open_failed_jobs(&self) -> Option<u32> {
+        self.max_failed_jobs?;
+        let state = self.state();
+        let open = state.open_until.is_some_and(|until| tokio::time::Instant::now() < until);
+        open.then_some(state.failed_jobs)
+    }
+
+    /// Records a job that gave up after repeated build failures.
+    ///
+    /// Rejects new jobs for the cooldown if too many jobs failed in a row.
+    fn on_job_failed(&self) {
+        let Some(max_failed_jobs) = self.max_failed_jobs else { return };
+        let mut state = self.state();
+        state.failed_jobs += 1;
+        if state.failed_jobs >= max_failed_jobs {
+            warn!(target: "payload_builder", failed_jobs = state.failed_jobs, cooldown = ?self.cooldown, "suspending payload building after repeated job failures");
+            state.open_until = Some(tokio::time::Instant::now() + self.cooldown);
         }
+    }
 
-        if let Some(best) = this.best_payload.take() {
-            debug!(target: "payload_builder", "resolving best payload");
-            return Poll::Ready(Ok(best))
+    /// Records a successful build, which resets the number of failed jobs.
+    fn on_build_succeeded(&self) {
+        if self.max_failed_jobs.is_some() {
+            *self.state() = PayloadCircuitBreakerState::default();
         }
+    }
+}
 
-        let mut empty_payload = this.empty_payload.take().expect("polled after completion");
-        match empty_payload.poll_unpin(cx) {
-            Poll::Ready(Ok(res)) => {
-                if let Err(err) = &res {
-                    warn!(target: "payload_builder", %err, "failed to resolve empty payload");
-                } else {
-                    debug!(target: "payload_builder", "resolving empty payload");
-                }
-                Poll::Ready(res)
-            }
-            Poll::Ready(Err(err)) => Poll::Ready(Err(err.into())),
-            Poll::Pending => {
-                this.empty_payload = Some(empty_payload);
-                Poll::Pending
-            }
+/// Number of better payloads buffered for each subscriber of a job, see
+/// [BasicPayloadJob::subscribe].
+const BETTER_PAYLOADS_CHANNEL_SIZE: usize = 16;
+
+/// Maximum number of empty payloads kept by an [EmptyPayloadCache].
+const EMPTY_PAYLOAD_CACHE_SIZE: usize = 16;
+
+/// Identifies the empty payload of a job by the fields of its config that the empty payload is
+/// built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EmptyPayloadKey {
+    /// The parent block of the payload.
+    parent_hash: B256,
+    /// The timestamp of the payload.
+    timestamp: u64,
+    /// The beneficiary of the payload.
+    suggested_fee_recipient: Address,
+    /// The prevrandao of the payload.
+    prev_randao: B256,
+    /// The parent beacon block root of the payload, if any.
+    parent_beacon_block_root: Option<B256>,
+    /// The root of the payload's withdrawals.
+    withdrawals_root: B256,
+    /// The extra data of the payload.
+    extra_data: Bytes,
+    /// The gas limit of the payload.
+    gas_limit: U256,
+    /// Whether the bundle state of the payload retains the reverts.
+    retain_reverts: bool,
+    /// Whether the payload may be built on the latest state.
+    latest_state_fallback: bool,
+}
+
+impl EmptyPayloadKey {
+    /// Returns the key of the empty payload of the given config.
+    ///
+    /// Returns `None` if the config has a [PayloadConfig::debug_bundle_state] hook, because the
+    /// hook must observe every built payload, so these payloads aren't cached.
+    fn new<Attributes: PayloadBuilderAttributes>(
+        config: &PayloadConfig<Attributes>,
+    ) -> Option<Self> {
+        if config.debug_bundle_state.is_some() {
+            return None
         }
+        Some(Self {
+            parent_hash: config.parent_block.hash(),
+            timestamp: config.attributes.timestamp(),
+            suggested_fee_recipient: config.attributes.suggested_fee_recipient(),
+            prev_randao: config.attributes.prev_randao(),
+            parent_beacon_block_root: config.attributes.parent_beacon_block_root(),
+            withdrawals_root: proofs::calculate_withdrawals_root(config.attributes.withdrawals()),
+            extra_data: config.extra_data(),
+            gas_limit: config.initialized_block_env.gas_limit,
+            retain_reverts: matches!(config.bundle_retention, BundleRetention::Reverts),
+            latest_state_fallback: config.latest_state_fallback,
+        })
     }
 }
 
-/// A future that resolves to the result of the block building job.
+/// A bounded cache of recently built empty payloads, shared by all jobs of a generator.
+///
+/// Jobs with the same [EmptyPayloadKey] build identical empty payloads, so this avoids computing
+/// the same state root repeatedly. All cached payloads build on the same parent block, inserting a
+/// payload for another parent clears the cache.
 #[derive(Debug)]
-struct PendingPayload<P> {
-    /// The marker to cancel the job on drop
-    _cancel: Cancelled,
-    /// The channel to send the result to.
-    payload: oneshot::Receiver<Result<BuildOutcome<P>, PayloadBuilderError>>,
+struct EmptyPayloadCache<Payload>(Arc<std::sync::Mutex<VecDeque<(EmptyPayloadKey, Payload)>>>);
+
+impl<Payload> EmptyPayloadCache<Payload> {
+    /// Locks the cached entries.
+    fn entries(&self) -> std::sync::MutexGuard<'_, VecDeque<(EmptyPayloadKey, Payload)>> {
+        self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the cached payload for the given key.
+    fn get(&self, key: &EmptyPayloadKey) -> Option<Payload>
+    where
+        Payload: Clone,
+    {
+        self.entries().iter().find(|(cached, _)| cached == key).map(|(_, payload)| payload.clone())
+    }
+
+    /// Caches the given payload, evicting the oldest payload if the cache is full.
+    fn insert(&self, key: EmptyPayloadKey, payload: Payload) {
+        let mut entries = self.entries();
+        if entries.front().is_some_and(|(cached, _)| cached.parent_hash != key.parent_hash) {
+            entries.clear();
+        }
+        if entries.iter().any(|(cached, _)| *cached == key) {
+            return
+        }
+        if entries.len() == EMPTY_PAYLOAD_CACHE_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back((key, payload));
+    }
 }
 
-impl<P> Future for PendingPayload<P> {
-    type Output = Result<BuildOutcome<P>, PayloadBuilderError>;
+impl<Payload> Clone for EmptyPayloadCache<Payload> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let res = ready!(self.payload.poll_unpin(cx));
-        Poll::Ready(res.map_err(Into::into).and_then(|res| res))
+impl<Payload> Default for EmptyPayloadCache<Payload> {
+    fn default() -> Self {
+        Self(Default::default())
     }
 }
 
-/// A marker that can be used to cancel a job.
+/// The config and best payload of an active [BasicPayloadJob].
+#[derive(Debug)]
+struct ActivePayloadJob<Attributes, Payload> {
+    config: PayloadConfig<Attributes>,
+    best_payload: Option<Payload>,
+}
+
+/// The active jobs of a generator by payload id.
 ///
-/// If dropped, it will set the `cancelled` flag to true.
-#[derive(Default, Clone, Debug)]
-pub struct Cancelled(Arc<AtomicBool>);
+/// Every job registers itself on creation, publishes its best payload and unregisters once it is
+/// dropped, see [ActivePayloadJobEntry].
+#[derive(Debug)]
+struct ActivePayloadJobs<Attributes, Payload>(
+    Arc<std::sync::Mutex<HashMap<PayloadId, ActivePayloadJob<Attributes, Payload>>>>,
+);
 
-// === impl Cancelled ===
+impl<Attributes, Payload> ActivePayloadJobs<Attributes, Payload> {
+    /// Locks the active jobs.
+    fn jobs(
+        &self,
+    ) -> std::sync::MutexGuard<'_, HashMap<PayloadId, ActivePayloadJob<Attributes, Payload>>> {
+        self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
 
-impl Cancelled {
-    /// Returns true if the job was cancelled.
-    pub fn is_cancelled(&self) -> bool {
-        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    /// Registers a job with the given config.
+    fn register(
+        &self,
+        config: &PayloadConfig<Attributes>,
+    ) -> ActivePayloadJobEntry<Attributes, Payload>
+    where
+        Attributes: PayloadBuilderAttributes + Clone,
+    {
+        let id = config.payload_id();
+        self.jobs().insert(id, ActivePayloadJob { config: config.clone(), best_payload: None });
+        ActivePayloadJobEntry { jobs: self.clone(), id }
+    }
+
+    /// Returns the config and best payload of the job with the given id.
+    fn get(&self, id: PayloadId) -> Option<(PayloadConfig<Attributes>, Option<Payload>)>
+    where
+        Attributes: Clone,
+        Payload: Clone,
+    {
+        self.jobs().get(&id).map(|job| (job.config.clone(), job.best_payload.clone()))
     }
 }
 
-impl Drop for Cancelled {
-    fn drop(&mut self) {
-        self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+impl<Attributes, Payload> Clone for ActivePayloadJobs<Attributes, Payload> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
     }
 }
 
-/// Static config for how to build a payload.
-#[derive(Clone, Debug)]
-pub struct PayloadConfig<Attributes> {
-    /// Pre-configured block environment.
-    pub initialized_block_env: BlockEnv,
-    /// Configuration for the environment.
-    pub initialized_cfg: CfgEnvWithHandlerCfg,
-    /// The parent block.
-    pub parent_block: Arc<SealedBlock>,
-    /// Block extra data.
-    pub extra_data: Bytes,
-    /// Requested attributes for the payload.
-    pub attributes: Attributes,
-    /// The chain spec.
-    pub chain_spec: Arc<ChainSpec>,
+impl<Attributes, Payload> Default for ActivePayloadJobs<Attributes, Payload> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
 }
 
-impl<Attributes> PayloadConfig<Attributes> {
-    /// Returns an owned instance of the [PayloadConfig]'s extra_data bytes.
-    pub fn extra_data(&self) -> Bytes {
-        self.extra_data.clone()
+/// The registration of a job in [ActivePayloadJobs], the job is unregistered on drop.
+#[derive(Debug)]
+struct ActivePayloadJobEntry<Attributes, Payload> {
+    jobs: ActivePayloadJobs<Attributes, Payload>,
+    id: PayloadId,
+}
+
+impl<Attributes, Payload> ActivePayloadJobEntry<Attributes, Payload> {
+    /// Publishes the new best payload of the job.
+    fn set_best_payload(&self, payload: Payload) {
+        if let Some(job) = self.jobs.jobs().get_mut(&self.id) {
+            job.best_payload = Some(payload);
+        }
     }
 }
 
-impl<Attributes> PayloadConfig<Attributes>
+impl<Attributes, Payload> Drop for ActivePayloadJobEntry<Attributes, Payload> {
+    fn drop(&mut self) {
+        self.jobs.jobs().remove(&self.id);
+    }
+}
+
+/// The state a generator shares with its jobs that depends on the payload types of the builder.
+struct SharedJobState<Pool, Client, Builder>
 where
-    Attributes: PayloadBuilderAttributes,
+    Builder: PayloadBuilder<Pool, Client>,
 {
-    /// Create new payload config.
-    pub fn new(
-        parent_block: Arc<SealedBlock>,
-        extra_data: Bytes,
-        attributes: Attributes,
-        chain_spec: Arc<ChainSpec>,
-    ) -> Self {
-        // configure evm env based on parent block
-        let (initialized_cfg, initialized_block_env) =
-            attributes.cfg_and_block_env(&chain_spec, &parent_block);
+    /// Empty payloads recently built by the jobs of the generator.
+    empty_payloads: EmptyPayloadCache<Builder::BuiltPayload>,
+    /// The jobs of the generator that haven't been dropped yet.
+    active_jobs: ActivePayloadJobs<Builder::Attributes, Builder::BuiltPayload>,
+    _marker: PhantomData<fn() -> (Pool, Client, Builder)>,
+}
 
+impl<Pool, Client, Builder> Default for SharedJobState<Pool, Client, Builder>
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    fn default() -> Self {
         Self {
-            initialized_block_env,
-            initialized_cfg,
-            parent_block,
-            extra_data,
-            attributes,
-            chain_spec,
+            empty_payloads: Default::default(),
+            active_jobs: Default::default(),
+            _marker: PhantomData,
         }
     }
-
-    /// Returns the payload id.
-    pub fn payload_id(&self) -> PayloadId {
-        self.attributes.payload_id()
-    }
 }
 
-/// The possible outcomes of a payload building attempt.
-#[derive(Debug)]
-pub enum BuildOutcome<Payload> {
-    /// Successfully built a better block.
-    Better {
-        /// The new payload that was built.
-        payload: Payload,
-        /// The cached reads that were used to build the payload.
-        cached_reads: CachedReads,
-    },
-    /// Aborted payload building because resulted in worse block wrt. fees.
-    Aborted {
-        /// The total fees associated with the attempted payload.
-        fees: U256,
-        /// The cached reads that were used to build the payload.
-        cached_reads: CachedReads,
-    },
-    /// Build job was cancelled
-    Cancelled,
+impl<Pool, Client, Builder> fmt::Debug for SharedJobState<Pool, Client, Builder>
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedJobState").finish_non_exhaustive()
+    }
 }
 
-/// A collection of arguments used for building payloads.
+/// Holds the [SharedJobState] of a generator.
 ///
-/// This struct encapsulates the essential components and configuration required for the payload
-/// building process. It holds references to the Ethereum client, transaction pool, cached reads,
-/// payload configuration, cancellation status, and the best payload achieved so far.
-#[derive(Debug)]
-pub struct BuildArguments<Pool, Client, Attributes, Payload> {
-    /// How to interact with the chain.
-    pub client: Client,
-    /// The transaction pool.
-    pub pool: Pool,
-    /// Previously cached disk reads
-    pub cached_reads: CachedReads,
-    /// How to configure the payload.
-    pub config: PayloadConfig<Attributes>,
-    /// A marker that can be used to cancel the job.
-    pub cancel: Cancelled,
-    /// The best payload achieved so far.
-    pub best_payload: Option<Payload>,
+/// The bound on the payload types of the builder is only required to access the state, so that
+/// the generator itself doesn't require [PayloadBuilder] bounds.
+trait JobState<Pool, Client, Builder>: fmt::Debug + Send + Sync {
+    /// Returns the state shared with the jobs of the generator.
+    fn state(&self) -> &SharedJobState<Pool, Client, Builder>
+    where
+        Builder: PayloadBuilder<Pool, Client>;
 }
 
-impl<Pool, Client, Attributes, Payload> BuildArguments<Pool, Client, Attributes, Payload> {
-    /// Create new build arguments.
-    pub fn new(
-        client: Client,
-        pool: Pool,
-        cached_reads: CachedReads,
-        config: PayloadConfig<Attributes>,
-        cancel: Cancelled,
-        best_payload: Option<Payload>,
-    ) -> Self {
-        Self { client, pool, cached_reads, config, cancel, best_payload }
+impl<Pool, Client, Builder> JobState<Pool, Client, Builder>
+    for SharedJobState<Pool, Client, Builder>
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    fn state(&self) -> &SharedJobState<Pool, Client, Builder> {
+        self
     }
 }
 
-/// A trait for building payloads that encapsulate Ethereum transactions.
-///
-/// This trait provides the `try_build` method to construct a transaction payload
-/// using `BuildArguments`. It returns a `Result` indicating success or a
-/// `PayloadBuilderError` if building fails.
-///
-/// Generic parameters `Pool` and `Client` represent the transaction pool and
-/// Ethereum client types.
-pub trait PayloadBuilder<Pool, Client>: Send + Sync + Clone {
-    /// The payload attributes type to accept for building.
-    type Attributes: PayloadBuilderAttributes;
-    /// The type of the built payload.
-    type BuiltPayload: BuiltPayload;
+/// Restricts how many generator tasks can be executed at once.
+#[derive(Debug, Clone)]
+struct PayloadTaskGuard {
+    /// The permits of the tasks.
+    semaphore: Arc<Semaphore>,
+    /// The maximum number of tasks.
+    max_payload_tasks: Arc<std::sync::Mutex<usize>>,
+    /// Number of permits held by tasks that are forgotten once released, because the maximum
+    /// number of tasks was lowered.
+    excess_permits: Arc<AtomicUsize>,
+}
 
-    /// Tries to build a transaction payload using provided arguments.
-    ///
-    /// Constructs a transaction payload based on the given arguments,
-    /// returning a `Result` indicating success or an error if building fails.
-    ///
-    /// # Arguments
-    ///
-    /// - `args`: Build arguments containing necessary components.
+// === impl PayloadTaskGuard ===
+
+impl PayloadTaskGuard {
+    fn new(max_payload_tasks: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_payload_tasks)),
+            max_payload_tasks: Arc::new(std::sync::Mutex::new(max_payload_tasks)),
+            excess_permits: Default::default(),
+        }
+    }
+
+    /// Waits for a permit to execute a task.
     ///
-    /// # Returns
+    /// Returns `None` if the semaphore was closed.
+    async fn acquire(&self) -> Option<PayloadTaskPermit<'_>> {
+        let permit = self.semaphore.acquire().await.ok()?;
+        Some(PayloadTaskPermit { permit: Some(permit), excess_permits: &self.excess_permits })
+    }
+
+    /// Returns the number of permits currently available to tasks.
+    fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Locks the maximum number of tasks.
+    fn max_payload_tasks(&self) -> std::sync::MutexGuard<'_, usize> {
+        self.max_payload_tasks.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Changes the maximum number of tasks to `max_payload_tasks`.
     ///
-    /// A `Result` indicating the build outcome or an error.
-    fn try_build(
-        &self,
-        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
-    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError>;
+    /// Lowering the maximum doesn't wait for running tasks: available permits are forgotten right
+    /// away and the remaining ones once the tasks holding them finish.
+    fn resize(&self, max_payload_tasks: usize) {
+        let mut max = self.max_payload_tasks();
+        let current = std::mem::replace(&mut *max, max_payload_tasks);
+        if max_payload_tasks >= current {
+            let added = max_payload_tasks - current;
+            // permits that weren't forgotten yet are kept instead of adding new ones
+            let excess = self
+                .excess_permits
+                .fetch_update(
+                    std::sync::atomic::Ordering::Relaxed,
+                    std::sync::atomic::Ordering::Relaxed,
+                    |excess| Some(excess.saturating_sub(added)),
+                )
+                .unwrap_or_default();
+            self.semaphore.add_permits(added - excess.min(added));
+        } else {
+            let removed = current - max_payload_tasks;
+            let forgotten = self.semaphore.forget_permits(removed);
+            self.excess_permits
+                .fetch_add(removed - forgotten, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+}
 
-    /// Invoked when the payload job is being resolved and there is no payload yet.
+/// A handle to change the maximum number of tasks the [BasicPayloadJob]s of a generator spawn for
+/// building payloads.
+///
+/// The handle is obtained before the generator is moved into the payload builder service, see
+/// [BasicPayloadJobGenerator::payload_tasks_handle].
+#[derive(Debug, Clone)]
+pub struct PayloadTasksHandle(PayloadTaskGuard);
+
+// === impl PayloadTasksHandle ===
+
+impl PayloadTasksHandle {
+    /// Sets the maximum number of tasks to spawn for building payloads.
     ///
-    /// If this returns a payload, it will be used as the final payload for the job.
+    /// Lowering the maximum doesn't wait for the running tasks, their permits are released once
+    /// they finish.
     ///
-    /// TODO(mattsse): This needs to be refined a bit because this only exists for OP atm
-    fn on_missing_payload(
-        &self,
-        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
-    ) -> Option<Self::BuiltPayload> {
-        let _args = args;
-        None
+    /// # Panics
+    ///
+    /// If `max_payload_tasks` is 0.
+    pub fn set_max_payload_tasks(&self, max_payload_tasks: usize) {
+        assert!(max_payload_tasks > 0, "max_payload_tasks must be greater than 0");
+        self.0.resize(max_payload_tasks);
     }
 
-    /// Builds an empty payload without any transaction.
-    fn build_empty_payload(
-        client: &Client,
-        config: PayloadConfig<Self::Attributes>,
-    ) -> Result<Self::BuiltPayload, PayloadBuilderError>;
+    /// Returns the maximum number of tasks to spawn for building payloads.
+    pub fn max_payload_tasks(&self) -> usize {
+        *self.0.max_payload_tasks()
+    }
 }
 
-/// Represents the outcome of committing withdrawals to the runtime database and post state.
-/// Pre-shanghai these are `None` values.
-#[derive(Default, Debug)]
-pub struct WithdrawalsOutcome {
-    /// committed withdrawals, if any.
-    pub withdrawals: Option<Withdrawals>,
-    /// withdrawals root if any.
-    pub withdrawals_root: Option<B256>,
+/// A permit to execute a task, see [PayloadTaskGuard].
+#[derive(Debug)]
+struct PayloadTaskPermit<'a> {
+    /// The permit, returned to the semaphore on drop unless it's an excess permit.
+    permit: Option<SemaphorePermit<'a>>,
+    /// See [PayloadTaskGuard::excess_permits].
+    excess_permits: &'a AtomicUsize,
 }
 
-impl WithdrawalsOutcome {
-    /// No withdrawals pre shanghai
-    pub fn pre_shanghai() -> Self {
-        Self { withdrawals: None, withdrawals_root: None }
+impl Drop for PayloadTaskPermit<'_> {
+    fn drop(&mut self) {
+        let excess = self
+            .excess_permits
+            .fetch_update(
+                std::sync::atomic::Ordering::Relaxed,
+                std::sync::atomic::Ordering::Relaxed,
+                |excess| excess.checked_sub(1),
+            )
+            .is_ok();
+        if let Some(permit) = self.permit.take().filter(|_| excess) {
+            permit.forget();
+        }
     }
+}
 
-    /// No withdrawals
-    pub fn empty() -> Self {
-        Self {
-            withdrawals: Some(Withdrawals::default()),
-            withdrawals_root: Some(EMPTY_WITHDRAWALS),
+/// Settings for the [BasicPayloadJobGenerator].
+#[derive(Debug, Clone)]
+pub struct BasicPayloadJobGeneratorConfig {
+    /// Data to include in the block's extra data field.
+    extradata: Bytes,
+    /// Target gas ceiling for built blocks, defaults to [ETHEREUM_BLOCK_GAS_LIMIT] gas.
+    max_gas_limit: u64,
+    /// Gas limit floor for built blocks, defaults to [MINIMUM_GAS_LIMIT] gas.
+    min_gas_limit: u64,
+    /// The interval at which the job should build a new payload after the last.
+    interval: Duration,
+    /// The deadline for when the payload builder job should resolve.
+    ///
+    /// By default this is [SLOT_DURATION]: 12s
+    deadline: Duration,
+    /// Maximum number of tasks to spawn for building a payload.
+    ///
+    /// This is the initial maximum, see [PayloadTasksHandle] to change it at runtime.
+    max_payload_tasks: usize,
+    /// Number of candidate payloads to build in parallel per interval.
+    candidates_per_build: usize,
+    /// How long resolving a job without a payload waits for the in progress build before
+    /// falling back to the empty payload.
+    resolve_wait_for_pending: Duration,
+    /// How long a build attempt may run before it's cancelled.
+    build_timeout: Option<Duration>,
+    /// Produces the extra data per job, overriding `extradata`.
+    extradata_fn: Option<ExtraDataFn>,
+    /// Number of consecutive aborted builds after which a job stops building.
+    max_stale_intervals: Option<u32>,
+    /// Maximum number of builds a job spawns, `0` for unlimited.
+    max_build_attempts: usize,
+    /// Inspects the bundle state of every built payload, for debugging.
+    debug_bundle_state: Option<BundleStateHook>,
+    /// Whether empty payloads fall back to the latest state if the parent state is missing.
+    latest_state_fallback: bool,
+    /// Maximum RLP encoded size of a built block.
+    max_encoded_block_size: Option<usize>,
+    /// Maximum approximate size of the cached reads of a job in bytes.
+    max_cached_reads_bytes: Option<usize>,
+    /// Whether jobs spawn their first build on creation.
+    eager_first_build: bool,
+    /// Whether jobs spawn a single build instead of one per interval.
+    build_once: bool,
+    /// Absolute upper bound of the duration of a job.
+    max_deadline: Option<Duration>,
+    /// Number of jobs failing in a row after which new jobs are rejected.
+    max_failed_jobs: Option<u32>,
+    /// How long new jobs are rejected once `max_failed_jobs` is reached.
+    failed_jobs_cooldown: Duration,
+    /// Decides whether a built payload replaces the best payload of a job.
+    ranking: Arc<dyn PayloadRanking>,
+}
+
+// === impl BasicPayloadJobGeneratorConfig ===
+
+impl BasicPayloadJobGeneratorConfig {
+    /// Returns the extra data for a new job.
+    ///
+    /// Falls back to the static extra data if the produced value exceeds
+    /// [MAXIMUM_EXTRA_DATA_SIZE] bytes.
+    fn job_extradata(&self) -> Bytes {
+        if let Some(extradata_fn) = &self.extradata_fn {
+            let extradata = (extradata_fn.0)();
+            if extradata.len() <= MAXIMUM_EXTRA_DATA_SIZE {
+                return extradata
+            }
+            warn!(target: "payload_builder", len = extradata.len(), "produced extra data exceeds the maximum size, using the static extra data");
         }
+        self.extradata.clone()
     }
-}
 
-/// Executes the withdrawals and commits them to the _runtime_ Database and BundleState.
-///
-/// Returns the withdrawals root.
-///
-/// Returns `None` values pre shanghai
-pub fn commit_withdrawals<DB: Database<Error = ProviderError>>(
-    db: &mut State<DB>,
-    chain_spec: &ChainSpec,
-    timestamp: u64,
-    withdrawals: Withdrawals,
-) -> RethResult<WithdrawalsOutcome> {
-    if !chain_spec.is_shanghai_active_at_timestamp(timestamp) {
-        return Ok(WithdrawalsOutcome::pre_shanghai())
+    /// Sets the interval at which the job should build a new payload after the last.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
     }
 
-    if withdrawals.is_empty() {
-        return Ok(WithdrawalsOutcome::empty())
+    /// Sets the deadline when this job should resolve.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
     }
 
-    let balance_increments =
-        post_block_withdrawals_balance_increments(chain_spec, timestamp, &withdrawals);
+    /// Sets an absolute upper bound for how long a job runs, regardless of the payload timestamp.
+    ///
+    /// Jobs run for the [BasicPayloadJobGeneratorConfig::deadline] past the payload timestamp,
+    /// which can be far in the future if the consensus client or the system clock is off. Defaults
+    /// to `None`, which only bounds the time until the payload timestamp by three deadlines.
+    pub fn max_deadline(mut self, max_deadline: Option<Duration>) -> Self {
+        self.max_deadline = max_deadline;
+        self
+    }
 
-    db.increment_balances(balance_increments)?;
+    /// Sets the number of jobs giving up in a row after which the generator rejects new jobs.
+    ///
+    /// A job gives up after [MAX_FATAL_BUILD_FAILURES] identical, potentially fatal build
+    /// failures, for example if the database is unavailable. Once the limit is reached, new jobs
+    /// fail with [PayloadBuilderError::BuildingSuspended] for the
+    /// [BasicPayloadJobGeneratorConfig::failed_jobs_cooldown]. The first job after the cooldown
+    /// probes whether building recovered: a successful build resets the count, another failed job
+    /// suspends building again. Defaults to `None`, which never rejects jobs.
+    pub fn max_failed_jobs(mut self, max_failed_jobs: Option<u32>) -> Self {
+        self.max_failed_jobs = max_failed_jobs;
+        self
+    }
 
-    let withdrawals_root = proofs::calculate_withdrawals_root(&withdrawals);
+    /// Sets how long new jobs are rejected after [BasicPayloadJobGeneratorConfig::max_failed_jobs]
+    /// jobs failed in a row.
+    ///
+    /// By default this is [SLOT_DURATION]: 12s
+    pub fn failed_jobs_cooldown(mut self, cooldown: Duration) -> Self {
+        self.failed_jobs_cooldown = cooldown;
+        self
+    }
 
-    // calculate withdrawals root
-    Ok(WithdrawalsOutcome {
-        withdrawals: Some(withdrawals),
-        withdrawals_root: Some(withdrawals_root),
-    })
-}
+    /// Sets the maximum number of tasks to spawn for building a payload(s).
+    ///
+    /// # Panics
+    ///
+    /// If `max_payload_tasks` is 0.
+    pub fn max_payload_tasks(mut self, max_payload_tasks: usize) -> Self {
+        assert!(max_payload_tasks > 0, "max_payload_tasks must be greater than 0");
+        self.max_payload_tasks = max_payload_tasks;
+        self
+    }
 
-/// Apply the [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788) pre block contract call.
-///
-/// This constructs a new [Evm] with the given DB, and environment
-/// ([CfgEnvWithHandlerCfg] and [BlockEnv]) to execute the pre block contract call.
-///
-/// The parent beacon block root used for the call is gathered from the given
-/// [PayloadBuilderAttributes].
-///
-/// This uses [apply_beacon_root_contract_call] to ultimately apply the beacon root contract state
-/// change.
-pub fn pre_block_beacon_root_contract_call<DB: Database + DatabaseCommit, Attributes>(
-    db: &mut DB,
-    chain_spec: &ChainSpec,
-    block_number: u64,
-    initialized_cfg: &CfgEnvWithHandlerCfg,
-    initialized_block_env: &BlockEnv,
-    attributes: &Attributes,
-) -> Result<(), PayloadBuilderError>
-where
-    DB::Error: std::fmt::Display,
-    Attributes: PayloadBuilderAttributes,
-{
-    // apply pre-block EIP-4788 contract call
-    let mut evm_pre_block = Evm::builder()
-        .with_db(db)
-        .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
-            initialized_cfg.clone(),
-            initialized_block_env.clone(),
-            Default::default(),
-        ))
-        .build();
+    /// Sets the number of candidate payloads to build in parallel per interval, of which the best
+    /// is kept.
+    ///
+    /// This is useful for builders with stochastic strategies. Every candidate occupies one of the
+    /// `max_payload_tasks`.
+    ///
+    /// # Panics
+    ///
+    /// If `candidates_per_build` is 0.
+    pub fn candidates_per_build(mut self, candidates_per_build: usize) -> Self {
+        assert!(candidates_per_build > 0, "candidates_per_build must be greater than 0");
+        self.candidates_per_build = candidates_per_build;
+        self
+    }
 
-    // initialize a block from the env, because the pre block call needs the block itself
-    apply_beacon_root_contract_call(
-        chain_spec,
-        attributes.timestamp(),
-        block_number,
-        attributes.parent_beacon_block_root(),
-        &mut evm_pre_block,
-    )
-    .map_err(|err| PayloadBuilderError::Internal(err.into()))
-}
+    /// Sets how long resolving a job that hasn't built a payload yet waits for the in progress
+    /// build, before it falls back to the empty payload.
+    ///
+    /// Defaults to zero, which resolves the empty payload as soon as it's built.
+    pub fn resolve_wait_for_pending(mut self, resolve_wait_for_pending: Duration) -> Self {
+        self.resolve_wait_for_pending = resolve_wait_for_pending;
+        self
+    }
 
-/// Checks if the new payload is better than the current best.
-///
-/// This compares the total fees of the blocks, higher is better.
-#[inline(always)]
-pub fn is_better_payload(best_payload: Option<impl BuiltPayload>, new_fees: U256) -> bool {
-    if let Some(best_payload) = best_payload {
-        new_fees > best_payload.fees()
-    } else {
-        true
+    /// Sets how long a build attempt may run before it's cancelled and fails with
+    /// [PayloadBuilderError::BuildTimedOut].
+    ///
+    /// The job retries on the next interval. Defaults to `None`, which doesn't limit the duration
+    /// of build attempts.
+    pub fn build_timeout(mut self, build_timeout: Option<Duration>) -> Self {
+        self.build_timeout = build_timeout;
+        self
     }
-}
 
-/// Returns the duration until the given unix timestamp in seconds.
-///
-/// Returns `Duration::ZERO` if the given timestamp is in the past.
-fn duration_until(unix_timestamp_secs: u64) -> Duration {
-    let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
-    let timestamp = Duration::from_secs(unix_timestamp_secs);
-    timestamp.saturating_sub(unix_now)
+    /// Sets the number of consecutive build attempts that don't improve the best payload, after
+    /// which a job stops spawning new builds.
+    ///
+    /// The job keeps serving its best payload until it resolves. Defaults to `None`, which builds
+    /// until the deadline.
+    pub fn max_stale_intervals(mut self, max_stale_intervals: Option<u32>) -> Self {
+        self.max_stale_intervals = max_stale_intervals;
+        self
+    }
+
+    /// Sets the maximum number of builds a job spawns.
+    ///
+    /// Once reached, the job stops spawning new builds but keeps serving its best payload until it
+    /// resolves. Defaults to `0`, which builds until the deadline.
+    pub fn max_build_attempts(mut self, max_build_attempts: usize) -> Self {
+        self.max_build_attempts = max_build_attempts;
+        self
+    }
+
+    /// Sets the data to include in the block's extra data field.
+    ///
+    /// Defaults to the current client version: `rlp(RETH_CLIENT_VERSION)`.
+    pub fn extradata(mut self, extradata: Bytes) -> Self {
+        self.extradata = extradata;
+        self
+    }
+
+    /// Sets a function that produces the extra data for every new job, overriding the static
+    /// [BasicPayloadJobGeneratorConfig::extradata].
+    ///
+    /// Values that exceed [MAXIMUM_EXTRA_DATA_SIZE] bytes are ignored in favor of the static extra
+    /// data.
+    pub fn extradata_fn(mut self, extradata_fn: Arc<dyn Fn() -> Bytes + Send + Sync>) -> Self {
+        self.extradata_fn = Some(ExtraDataFn(extradata_fn));
+        self
+    }
+
+    /// Sets a callback that receives the bundle state of every built payload before its state root
+    /// is computed, see [PayloadConfig::debug_bundle_state].
+    ///
+    /// This is intended for debugging state root mismatches and disabled by default, because
+    /// builders may need to copy the bundle state for it.
+    pub fn debug_bundle_state(
+        mut self,
+        hook: Arc<dyn Fn(&BundleStateWithReceipts) + Send + Sync>,
+    ) -> Self {
+        self.debug_bundle_state = Some(BundleStateHook(hook));
+        self
+    }
+
+    /// Sets whether empty payloads are built on the latest state if the state of the parent block
+    /// isn't available, see [PayloadConfig::latest_state_fallback].
+    ///
+    /// The empty payload is then built on approximated state and may be invalid, so this is only
+    /// intended for development. Defaults to `false`.
+    pub fn latest_state_fallback(mut self, latest_state_fallback: bool) -> Self {
+        self.latest_state_fallback = latest_state_fallback;
+        self
+    }
+
+    /// Sets whether jobs spawn their first build when they're created, instead of when they're
+    /// first polled.
+    ///
+    /// This makes it more likely that a full payload is available early. Defaults to `false`.
+    pub fn eager_first_build(mut self, eager_first_build: bool) -> Self {
+        self.eager_first_build = eager_first_build;
+        self
+    }
+
+    /// Sets whether jobs spawn exactly one build when they're first polled, instead of a new build
+    /// every interval.
+    ///
+    /// The job doesn't retry a failed build and keeps its payload until it resolves. Together with
+    /// a manually driven deadline this makes the outcome of a job deterministic, which is useful
+    /// for tests. Defaults to `false`.
+    pub fn build_once(mut self, build_once: bool) -> Self {
+        self.build_once = build_once;
+        self
+    }
+
+    /// Sets how jobs decide whether a built payload replaces their best payload.
+    ///
+    /// Defaults to [FeeRanking].
+    pub fn ranking(mut self, ranking: impl PayloadRanking + 'static) -> Self {
+        self.ranking = Arc::new(ranking);
+        self
+    }
+
+    /// Sets the maximum RLP encoded size of built blocks in bytes.
+    ///
+    /// Payloads with a larger block are rejected and the job keeps its previous best payload.
+    /// Defaults to `None`, which doesn't limit the block size.
+    pub fn max_encoded_block_size(mut self, max_encoded_block_size: Option<usize>) -> Self {
+        self.max_encoded_block_size = max_encoded_block_size;
+        self
+    }
+
+    /// Sets the maximum approximate size in bytes of the state a job caches across its builds.
+    ///
+    /// If the cached reads exceed the limit after a build, they're dropped and the next build
+    /// starts with an empty cache. See [CachedReads::approx_size_bytes]. Defaults to `None`, which
+    /// doesn't limit the cache.
+    pub fn max_cached_reads_bytes(mut self, max_cached_reads_bytes: Option<usize>) -> Self {
+        self.max_cached_reads_bytes = max_cached_reads_bytes;
+        self
+    }
+
+    /// Sets the target gas ceiling for mined blocks.
+    ///
+    /// Defaults to [ETHEREUM_BLOCK_GAS_LIMIT] gas.
+    pub fn max_gas_limit(mut self, max_gas_limit: u64) -> Self {
+        self.max_gas_limit = max_gas_limit;
+        self
+    }
+
+    /// Sets the minimum gas limit of built blocks.
+    ///
+    /// If the block environment of a job has a lower gas limit, it's raised to this floor.
+    ///
+    /// Defaults to [MINIMUM_GAS_LIMIT] gas.
+    pub fn min_gas_limit(mut self, min_gas_limit: u64) -> Self {
+        self.min_gas_limit = min_gas_limit;
+        self
+    }
+}
+
+impl Default for BasicPayloadJobGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            extradata: alloy_rlp::encode(RETH_CLIENT_VERSION.as_bytes()).into(),
+            max_gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            min_gas_limit: MINIMUM_GAS_LIMIT,
+            interval: Duration::from_secs(1),
+            // 12s slot time
+            deadline: SLOT_DURATION,
+            max_payload_tasks: 3,
+            candidates_per_build: 1,
+            resolve_wait_for_pending: Duration::ZERO,
+            build_timeout: None,
+            extradata_fn: None,
+            max_stale_intervals: None,
+            max_build_attempts: 0,
+            debug_bundle_state: None,
+            latest_state_fallback: false,
+            max_encoded_block_size: None,
+            max_cached_reads_bytes: None,
+            eager_first_build: false,
+            build_once: false,
+            max_deadline: None,
+            max_failed_jobs: None,
+            failed_jobs_cooldown: SLOT_DURATION,
+            ranking: Arc::new(FeeRanking),
+        }
+    }
+}
+
+/// Produces the extra data of a job, see [BasicPayloadJobGeneratorConfig::extradata_fn].
+#[derive(Clone)]
+struct ExtraDataFn(Arc<dyn Fn() -> Bytes + Send + Sync>);
+
+impl fmt::Debug for ExtraDataFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtraDataFn").finish_non_exhaustive()
+    }
+}
+
+/// A callback that inspects the bundle state of a payload before it's sealed.
+///
+/// See [BasicPayloadJobGeneratorConfig::debug_bundle_state].
+#[derive(Clone)]
+pub struct BundleStateHook(Arc<dyn Fn(&BundleStateWithReceipts) + Send + Sync>);
+
+impl BundleStateHook {
+    /// Creates a new hook from the given callback.
+    pub fn new(hook: impl Fn(&BundleStateWithReceipts) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Invokes the callback with the given bundle state.
+    pub fn call(&self, bundle: &BundleStateWithReceipts) {
+        (self.0)(bundle)
+    }
+}
+
+impl fmt::Debug for BundleStateHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundleStateHook").finish_non_exhaustive()
+    }
+}
+
+/// A basic payload job that continuously builds a payload with the best transactions from the pool.
+#[derive(Debug)]
+pub struct BasicPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Builder: PayloadBuilder<Pool, Client>,
+{
+    /// The configuration for how the payload will be created.
+    config: PayloadConfig<Builder::Attributes>,
+    /// The client that can interact with the chain.
+    client: Client,
+    /// The transaction pool.
+    pool: Pool,
+    /// How to spawn building tasks
+    executor: Tasks,
+    /// The deadline when this job should resolve.
+    deadline: Pin<Box<Sleep>>,
+    /// The interval at which the job should build a new payload after the last.
+    interval: Interval,
+    /// The best payload so far.
+    best_payload: Option<Builder::BuiltPayload>,
+    /// Receiver for the block that is currently being built.
+    pending_block: Option<PendingPayload<Builder::BuiltPayload>>,
+    /// Restricts how many generator tasks can be executed at once.
+    payload_task_guard: PayloadTaskGuard,
+    /// Caches all disk reads for the state the new payloads builds on
+    ///
+    /// This is used to avoid reading the same state over and over again when new attempts are
+    /// triggered, because during the building process we'll repeatedly execute the transactions.
+    cached_reads: Option<CachedReads>,
+    /// metrics for this type
+    metrics: PayloadBuilderMetrics,
+    /// The type responsible for building payloads.
+    ///
+    /// See [PayloadBuilder]
+    builder: Builder,
+    /// The registration of this job in the job controls of the generator.
+    control: PayloadJobControlEntry,
+    /// Whether the generator was shut down, which stops building, see
+    /// [PayloadJobShutdownHandle::shutdown].
+    shutdown: PayloadJobShutdownHandle,
+    /// Woken when the generator is shut down.
+    shutdown_waker: Arc<AtomicWaker>,
+    /// State the payload is expected to touch, prewarmed into the cached reads before the first
+    /// build attempt.
+    access_list: Option<AccessList>,
+    /// Number of candidate payloads to build in parallel per interval.
+    candidates_per_build: usize,
+    /// Build statistics of this job.
+    stats: JobStats,
+    /// Whether [PayloadBuilder::on_job_start] was already invoked.
+    started: bool,
+    /// How long resolving without a payload waits for the in progress build.
+    resolve_wait_for_pending: Duration,
+    /// How long a build attempt may run before it's cancelled.
+    build_timeout: Option<Duration>,
+    /// Number of consecutive aborted builds after which no new builds are spawned.
+    max_stale_intervals: Option<u32>,
+    /// Number of consecutive aborted builds since the last better payload.
+    stale_intervals: u32,
+    /// Maximum number of builds the job spawns, `0` for unlimited.
+    max_build_attempts: usize,
+    /// Number of builds the job spawned.
+    build_attempts: usize,
+    /// Notifies subscribers of every better payload, created by the first subscriber.
+    better_payloads: Option<broadcast::Sender<Arc<Builder::BuiltPayload>>>,
+    /// Empty payloads recently built by the jobs of the generator.
+    empty_payloads: EmptyPayloadCache<Builder::BuiltPayload>,
+    /// Decides whether a built payload replaces the best payload.
+    checks: PayloadChecks,
+    /// Maximum approximate size of the cached reads in bytes.
+    max_cached_reads_bytes: Option<usize>,
+    /// The registration of this job in the active jobs of the generator.
+    active: ActivePayloadJobEntry<Builder::Attributes, Builder::BuiltPayload>,
+    /// The last potentially fatal build failure and how often it occurred in a row.
+    fatal_failures: Option<(String, u32)>,
+    /// Records whether the jobs of the generator keep failing.
+    circuit_breaker: PayloadCircuitBreaker,
+    /// When the last build attempt was spawned.
+    last_spawn_instant: Option<tokio::time::Instant>,
+    /// When the job was created.
+    created_at: tokio::time::Instant,
+    /// Whether the time until the first better payload was recorded.
+    first_payload_recorded: bool,
+    /// Whether only a single build is spawned.
+    build_once: bool,
+    /// The pool fingerprint of the last build, see [PayloadBuilder::pool_fingerprint].
+    last_fingerprint: Option<u64>,
+}
+
+impl<Client, Pool, Tasks, Builder> BasicPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    /// Returns the configuration the payloads of this job are built with.
+    pub fn config(&self) -> &PayloadConfig<Builder::Attributes> {
+        &self.config
+    }
+
+    /// Returns the best payload so far in the engine API representation.
+    ///
+    /// The payload version is selected by the forks active at the payload's timestamp. Like
+    /// [PayloadJob::best_payload], this builds an empty payload if nothing was built yet.
+    pub fn best_execution_payload(&self) -> Result<ExecutionPayload, PayloadBuilderError> {
+        let block = self.best_payload()?.block().clone();
+        let chain_spec = &self.config.chain_spec;
+        let timestamp = self.config.attributes.timestamp();
+        let payload = if chain_spec.is_cancun_active_at_timestamp(timestamp) {
+            ExecutionPayload::V3(block_to_payload_v3(block))
+        } else if chain_spec.is_shanghai_active_at_timestamp(timestamp) {
+            ExecutionPayload::V2(try_block_to_payload_v2(block))
+        } else {
+            ExecutionPayload::V1(try_block_to_payload_v1(block))
+        };
+        Ok(payload)
+    }
+
+    /// Returns a snapshot of the build statistics of this job.
+    pub fn metrics_snapshot(&self) -> JobMetrics {
+        self.stats.snapshot(self.best_payload.as_ref().map(|payload| payload.fees()))
+    }
+
+    /// Returns the fees the best payload gained over an empty payload, which has no fees.
+    ///
+    /// This is zero if no payload was built yet. The value is recorded in the
+    /// `payloads.value_over_empty` histogram when the job is resolved.
+    pub fn value_over_empty(&self) -> U256 {
+        self.best_payload.as_ref().map_or(U256::ZERO, |payload| payload.fees())
+    }
+
+    /// Returns a receiver for every better payload the job builds from now on.
+    ///
+    /// The channel is bounded: a subscriber that falls behind misses the oldest payloads instead of
+    /// stalling the job, see [broadcast::error::RecvError::Lagged].
+    pub fn subscribe(&mut self) -> broadcast::Receiver<Arc<Builder::BuiltPayload>> {
+        self.better_payloads
+            .get_or_insert_with(|| broadcast::channel(BETTER_PAYLOADS_CHANNEL_SIZE).0)
+            .subscribe()
+    }
+
+    /// Resets the number of consecutive build attempts that didn't improve the best payload.
+    ///
+    /// This resumes building of a job that stopped after
+    /// [BasicPayloadJobGeneratorConfig::max_stale_intervals], for example because new valuable
+    /// transactions arrived.
+    pub fn reset_stale_intervals(&mut self) {
+        self.stale_intervals = 0;
+    }
+
+    /// Returns true if the job stopped building because its best payload didn't improve for
+    /// [BasicPayloadJobGeneratorConfig::max_stale_intervals] consecutive builds.
+    fn is_stale(&self) -> bool {
+        self.max_stale_intervals.is_some_and(|max| self.stale_intervals >= max)
+    }
+
+    /// Invokes [PayloadBuilder::on_job_start] if it wasn't invoked yet.
+    fn ensure_started(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.builder.on_job_start(&self.config);
+        }
+    }
+
+    /// Spawns a new payload build task that sends its outcome to the given channel.
+    fn spawn_build_task(
+        &self,
+        cancel: Cancelled,
+        mut cached_reads: CachedReads,
+        access_list: Option<AccessList>,
+        tx: oneshot::Sender<Result<BuildOutcome<Builder::BuiltPayload>, PayloadBuilderError>>,
+    ) {
+        let client = self.client.clone();
+        let pool = self.pool.clone();
+        let guard = self.payload_task_guard.clone();
+        let payload_config = self.config.clone();
+        let best_payload = self.best_payload.clone();
+        let builder = self.builder.clone();
+        let metrics = self.metrics.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            // acquire the permit for executing the task
+            let acquire_started = tokio::time::Instant::now();
+            let permit = guard.acquire().await;
+            metrics.record_task_acquire_duration(acquire_started.elapsed());
+            metrics.set_task_permits_available(guard.available_permits());
+            if let Some(access_list) = access_list {
+                prewarm_cached_reads(
+                    &client,
+                    payload_config.parent_block.hash(),
+                    &mut cached_reads,
+                    access_list,
+                );
+            }
+            let args = BuildArguments {
+                client,
+                pool,
+                cached_reads,
+                config: payload_config,
+                cancel,
+                best_payload,
+            };
+            let result = builder.try_build(args);
+            drop(permit);
+            metrics.set_task_permits_available(guard.available_permits());
+            let _ = tx.send(result);
+        }));
+    }
+
+    /// Spawns the configured number of candidate builds and returns the pending best outcome.
+    fn spawn_build(&mut self) -> PendingPayload<Builder::BuiltPayload> {
+        self.metrics.inc_initiated_payload_builds();
+        self.stats.on_build_started();
+        // the time between spawns exceeds the interval if builds take longer than the interval
+        let now = tokio::time::Instant::now();
+        if let Some(last_spawn) = self.last_spawn_instant.replace(now) {
+            self.metrics.record_build_interval_actual(now - last_spawn);
+        }
+        let dry_run = self.control.take_dry_run_request();
+        if let Some(forced_top_of_block) = self.control.take_forced_top_of_block() {
+            self.config.forced_top_of_block = forced_top_of_block;
+        }
+        // a dry run must not consume the state of the job
+        let (cached_reads, access_list) = if dry_run {
+            (self.cached_reads.clone().unwrap_or_default(), self.access_list.clone())
+        } else {
+            (self.cached_reads.take().unwrap_or_default(), self.access_list.take())
+        };
+        let cached_reads_size = cached_reads.approx_size_bytes();
+        let timeout = self.build_timeout.map(|timeout| Box::pin(tokio::time::sleep(timeout)));
+        let (mut tx, rx) = oneshot::channel();
+        let cancel = Cancelled::default();
+        let _cancel = cancel.clone();
+
+        if self.candidates_per_build <= 1 {
+            self.spawn_build_task(cancel, cached_reads, access_list, tx);
+            return PendingPayload {
+                _cancel,
+                dry_run,
+                cached_reads_size,
+                started: now,
+                timeout,
+                payload: rx,
+            }
+        }
+
+        // every candidate gets its own marker, because a finished candidate cancels its marker
+        let mut markers = Vec::with_capacity(self.candidates_per_build);
+        let mut candidates = Vec::with_capacity(self.candidates_per_build);
+        for _ in 0..self.candidates_per_build {
+            let (candidate_tx, candidate_rx) = oneshot::channel();
+            let candidate_cancel = Cancelled::default();
+            markers.push(candidate_cancel.clone());
+            self.spawn_build_task(
+                candidate_cancel,
+                cached_reads.clone(),
+                access_list.clone(),
+                candidate_tx,
+            );
+            candidates.push(candidate_rx);
+        }
+
+        self.executor.spawn(Box::pin(async move {
+            let outcomes = {
+                let closed = std::pin::pin!(tx.closed());
+                let all = futures_util::future::join_all(candidates);
+                match futures_util::future::select(all, closed).await {
+                    futures_util::future::Either::Left((outcomes, _)) => outcomes,
+                    // the job dropped the pending build, dropping the markers cancels all
+                    // candidates
+                    futures_util::future::Either::Right(_) => return,
+                }
+            };
+            drop(markers);
+            let outcomes =
+                outcomes.into_iter().map(|res| res.map_err(Into::into).and_then(|res| res));
+            let _ = tx.send(best_candidate_outcome(outcomes));
+        }));
+
+        PendingPayload { _cancel, dry_run, cached_reads_size, started: now, timeout, payload: rx }
+    }
+
+    /// Handles a payload built by a finished build attempt, which replaces the best payload if
+    /// it's ranked better.
+    ///
+    /// `is_empty` is set for [BuildOutcome::Empty] payloads, which are also cached as the empty
+    /// payload of the job.
+    fn on_built_payload(
+        &mut self,
+        payload: Builder::BuiltPayload,
+        cached_reads: CachedReads,
+        is_empty: bool,
+        dry_run: bool,
+    ) {
+        let rejected = self.checks.check(&payload, self.best_payload.as_ref(), dry_run).err();
+        if rejected.map_or(true, PayloadRejection::is_committed) {
+            if is_empty {
+                trace!(target: "payload_builder", "built empty payload, the pool had no transactions");
+                if let Some(key) = EmptyPayloadKey::new(&self.config) {
+                    self.empty_payloads.insert(key, payload.clone());
+                }
+            }
+            self.cached_reads = Some(cached_reads);
+        }
+
+        match rejected {
+            Some(PayloadRejection::WrongParent) => {
+                let parent_hash = payload.block().parent_hash;
+                let expected = self.checks.parent_hash;
+                debug_assert_eq!(parent_hash, expected, "payload built on wrong parent");
+                warn!(target: "payload_builder", %parent_hash, %expected, "rejected payload built on wrong parent");
+                self.metrics.inc_failed_payload_builds();
+                self.stats.inc_failed_builds();
+            }
+            Some(PayloadRejection::DryRun) => {
+                debug!(target: "payload_builder", value = %payload.fees(), "built dry run payload");
+            }
+            Some(PayloadRejection::Identical) => {
+                // the builder rebuilt the best payload, nothing to replace
+                self.stale_intervals += 1;
+                self.metrics.inc_redundant_better();
+                trace!(target: "payload_builder", hash = %payload.block().hash(), "skipped better payload identical to the best payload");
+            }
+            Some(PayloadRejection::Oversized) => {
+                self.stale_intervals += 1;
+                self.metrics.inc_builds_rejected_oversize();
+                debug!(target: "payload_builder", size = payload.block().length(), max_size = ?self.checks.max_encoded_block_size, "rejected oversized payload");
+            }
+            Some(PayloadRejection::NotRankedBetter) => {
+                self.stale_intervals += 1;
+                self.metrics.inc_builds_aborted_worse();
+                trace!(target: "payload_builder", worse_fees = %payload.fees(), "discarded payload that isn't ranked better");
+            }
+            None => {
+                let tx_count = payload.block().body.len();
+                debug!(target: "payload_builder", value = %payload.fees(), tx_count, "built better payload");
+                self.metrics.record_payload_tx_count(tx_count);
+                if let Some(withdrawals) = &payload.block().withdrawals {
+                    self.metrics.record_withdrawals_applied(withdrawals.len());
+                }
+                if !self.first_payload_recorded {
+                    self.first_payload_recorded = true;
+                    self.metrics.record_time_to_first_block(self.created_at.elapsed());
+                }
+                self.stale_intervals = 0;
+                self.builder.on_better_payload(&payload);
+                self.active.set_best_payload(payload.clone());
+                if let Some(better_payloads) = &self.better_payloads {
+                    // fails if there are no subscribers
+                    let _ = better_payloads.send(Arc::new(payload.clone()));
+                }
+                self.best_payload = Some(payload);
+            }
+        }
+    }
+}
+
+impl<Client, Pool, Tasks, Builder> Future for BasicPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    type Output = Result<(), PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.ensure_started();
+
+        this.shutdown_waker.register(cx.waker());
+        let is_shutdown = this.shutdown.is_shutdown();
+        if is_shutdown && this.pending_block.take().is_some() {
+            // dropping the pending build cancels it
+            trace!(target: "payload_builder", "payload builder shut down, cancelled pending build");
+        }
+
+        // check if the deadline is reached
+        if this.deadline.as_mut().poll(cx).is_ready() {
+            trace!(target: "payload_builder", "payload building deadline reached");
+            return Poll::Ready(Ok(()))
+        }
+
+        if is_shutdown {
+            // keep the job without building until its best payload is resolved
+            return Poll::Pending
+        }
+
+        if this.build_once {
+            // the only build is spawned right away, not on an interval tick
+            if this.last_spawn_instant.is_none() && !this.control.is_paused() {
+                trace!(target: "payload_builder", "spawn single payload build task");
+                this.pending_block = Some(this.spawn_build());
+            }
+        } else {
+            // check if the interval is reached
+            while this.interval.poll_tick(cx).is_ready() {
+                if this.control.is_paused() {
+                    trace!(target: "payload_builder", "payload building paused, skipping interval");
+                    continue
+                }
+
+                if this.is_stale() {
+                    trace!(target: "payload_builder", stale_intervals = this.stale_intervals, "best payload didn't improve, skipping interval");
+                    continue
+                }
+
+                if this.max_build_attempts != 0 && this.build_attempts >= this.max_build_attempts {
+                    trace!(target: "payload_builder", build_attempts = this.build_attempts, "reached maximum build attempts, skipping interval");
+                    continue
+                }
+
+                // start a new job if there is no pending block and we haven't reached the deadline
+                if this.pending_block.is_none() {
+                    let fingerprint = this.builder.pool_fingerprint(&this.pool);
+                    if fingerprint.is_some() &&
+                        fingerprint == this.last_fingerprint &&
+                        !this.control.is_dry_run_requested() &&
+                        !this.control.has_forced_top_of_block()
+                    {
+                        trace!(target: "payload_builder", "best transactions unchanged, skipping interval");
+                        this.metrics.inc_builds_skipped_unchanged();
+                        continue
+                    }
+                    this.last_fingerprint = fingerprint;
+                    trace!(target: "payload_builder", "spawn new payload build task");
+                    this.build_attempts += 1;
+                    this.pending_block = Some(this.spawn_build());
+                }
+            }
+        }
+
+        // poll the pending block
+        if let Some(mut fut) = this.pending_block.take() {
+            match fut.poll_unpin(cx) {
+                Poll::Ready(Ok(outcome)) => {
+                    this.interval.reset();
+                    this.stats.on_build_finished();
+                    this.fatal_failures = None;
+                    this.circuit_breaker.on_build_succeeded();
+                    let is_empty = matches!(outcome, BuildOutcome::Empty { .. });
+                    if let BuildOutcome::Better { cached_reads, .. } |
+                    BuildOutcome::Empty { cached_reads, .. } |
+                    BuildOutcome::Aborted { cached_reads, .. } = &outcome
+                    {
+                        // a large delta every interval means the builds touch different state
+                        let size = cached_reads.approx_size_bytes();
+                        trace!(
+                            target: "payload_builder",
+                            cached_reads_size = size,
+                            cached_reads_delta = size as i64 - fut.cached_reads_size as i64,
+                            "build attempt finished"
+                        );
+                    }
+                    match outcome {
+                        BuildOutcome::Better { payload, cached_reads } |
+                        BuildOutcome::Empty { payload, cached_reads } => {
+                            this.on_built_payload(payload, cached_reads, is_empty, fut.dry_run)
+                        }
+                        BuildOutcome::Aborted { fees, cached_reads } => {
+                            if !fut.dry_run {
+                                this.cached_reads = Some(cached_reads);
+                                this.stale_intervals += 1;
+                            }
+                            this.metrics.inc_builds_aborted_worse();
+                            trace!(target: "payload_builder", worse_fees = %fees, "skipped payload build of worse block");
+                        }
+                        BuildOutcome::Cancelled => {
+                            this.metrics.inc_builds_cancelled();
+                            trace!(target: "payload_builder", "payload build cancelled");
+                        }
+                    }
+
+                    if let Some(max) = this.max_cached_reads_bytes {
+                        let size = this.cached_reads.as_ref().map_or(0, |c| c.approx_size_bytes());
+                        if size > max {
+                            debug!(target: "payload_builder", size, max, "dropping cached reads that exceed the size limit");
+                            this.cached_reads = None;
+                            this.metrics.inc_cached_reads_evicted();
+                        }
+                    }
+                }
+                Poll::Ready(Err(error)) => {
+                    // job failed, but we simply try again next interval
+                    if error.is_retryable() {
+                        trace!(target: "payload_builder", %error, "payload build attempt failed, retrying next interval");
+                    } else {
+                        debug!(target: "payload_builder", %error, "payload build attempt failed");
+                    }
+                    this.metrics.inc_failed_payload_builds();
+                    this.stats.on_build_finished();
+                    this.stats.inc_failed_builds();
+                    // retry the build even if the pool didn't change
+                    this.last_fingerprint = None;
+
+                    if error.is_potentially_fatal() {
+                        // a deterministic failure would fail every following build the same way
+                        let message = error.to_string();
+                        let failures = match this.fatal_failures.take() {
+                            Some((last, failures)) if last == message => failures + 1,
+                            _ => 1,
+                        };
+                        if failures >= MAX_FATAL_BUILD_FAILURES {
+                            warn!(target: "payload_builder", %error, failures, "giving up payload job after repeated build failures");
+                            this.circuit_breaker.on_job_failed();
+                            return Poll::Ready(Err(error))
+                        }
+                        this.fatal_failures = Some((message, failures));
+                    } else {
+                        this.fatal_failures = None;
+                    }
+                }
+                Poll::Pending => {
+                    this.pending_block = Some(fut);
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Client, Pool, Tasks, Builder> PayloadJob for BasicPayloadJob<Client, Pool, Tasks, Builder>
+where
+    Client: StateProviderFactory + Clone + Unpin + 'static,
+    Pool: TransactionPool + Unpin + 'static,
+    Tasks: TaskSpawner + Clone + 'static,
+    Builder: PayloadBuilder<Pool, Client> + Unpin + 'static,
+    <Builder as PayloadBuilder<Pool, Client>>::Attributes: Unpin + Clone,
+    <Builder as PayloadBuilder<Pool, Client>>::BuiltPayload: Unpin + Clone,
+{
+    type PayloadAttributes = Builder::Attributes;
+    type ResolvePayloadFuture = ResolveBestPayload<Self::BuiltPayload>;
+    type BuiltPayload = Builder::BuiltPayload;
+
+    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        if let Some(ref payload) = self.best_payload {
+            return Ok(payload.clone())
+        }
+        // No payload has been built yet, but we need to return something that the CL then can
+        // deliver, so we need to return an empty payload.
+        //
+        // Note: it is assumed that this is unlikely to happen, as the payload job is started right
+        // away and the first full block should have been built by the time CL is requesting the
+        // payload.
+        self.metrics.inc_requested_empty_payload();
+        self.stats.inc_empty_requests();
+        let key = EmptyPayloadKey::new(&self.config);
+        if let Some(payload) = key.as_ref().and_then(|key| self.empty_payloads.get(key)) {
+            trace!(target: "payload_builder", id=%self.config.payload_id(), "serving cached empty payload");
+            return Ok(payload)
+        }
+        let mut cached_reads = self.cached_reads.clone().unwrap_or_default();
+        let payload = Builder::build_empty_payload_with_cached_reads(
+            &self.client,
+            self.config.clone(),
+            &mut cached_reads,
+        )?;
+        if let Some(key) = key {
+            self.empty_payloads.insert(key, payload.clone());
+        }
+        Ok(payload)
+    }
+
+    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
+        Ok(self.config.attributes.clone())
+    }
+
+    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        self.metrics.record_value_over_empty(self.value_over_empty());
+        let best_payload = self.best_payload.take();
+        let maybe_better = self.pending_block.take();
+        let mut empty_payload = None;
+
+        if best_payload.is_none() {
+            debug!(target: "payload_builder", id=%self.config.payload_id(), "no best payload yet to resolve, building empty payload");
+
+            let cached_reads = self.cached_reads.take().unwrap_or_default();
+            let args = BuildArguments {
+                client: self.client.clone(),
+                pool: self.pool.clone(),
+                cached_reads: cached_reads.clone(),
+                config: self.config.clone(),
+                cancel: Cancelled::default(),
+                best_payload: None,
+            };
+
+            // TODO: create optimism payload job, that wraps this type, that implements PayloadJob
+            // with this branch. remove this branch from the non-op code. remove
+            // `on_missing_payload` requirement from builder trait
+            if let Some(payload) = self.builder.on_missing_payload(args) {
+                debug!(target: "payload_builder", id=%self.config.payload_id(), "resolving fallback payload as best payload");
+                return (
+                    ResolveBestPayload {
+                        best_payload: Some(payload),
+                        maybe_better,
+                        empty_payload,
+                        wait_for_pending: None,
+                        checks: self.checks.clone(),
+                    },
+                    KeepPayloadJobAlive::Yes,
+                )
+            }
+
+            // if no payload has been built yet
+            self.metrics.inc_requested_empty_payload();
+            self.stats.inc_empty_requests();
+            // no payload built yet, so we need to return an empty payload
+            empty_payload = Some(spawn_empty_payload::<_, Pool, _, Builder>(
+                &self.executor,
+                self.client.clone(),
+                self.config.clone(),
+                cached_reads,
+                &self.empty_payloads,
+            ));
+        }
+
+        let wait_for_pending = (best_payload.is_none() &&
+            maybe_better.is_some() &&
+            !self.resolve_wait_for_pending.is_zero())
+        .then(|| Box::pin(tokio::time::sleep(self.resolve_wait_for_pending)));
+        let fut = ResolveBestPayload {
+            best_payload,
+            maybe_better,
+            empty_payload,
+            wait_for_pending,
+            checks: self.checks.clone(),
+        };
+
+        (fut, KeepPayloadJobAlive::No)
+    }
+}
+
+/// The future that returns the best payload to be served to the consensus layer.
+///
+/// This returns the payload that's supposed to be sent to the CL.
+///
+/// If payload has been built so far, it will return that, but it will check if there's a better
+/// payload available from an in progress build job. If so it will return that.
+///
+/// If no payload has been built so far, it will either return an empty payload or the result of the
+/// in progress build job, whatever finishes first.
+#[derive(Debug)]
+pub struct ResolveBestPayload<Payload> {
+    /// Best payload so far.
+    best_payload: Option<Payload>,
+    /// Regular payload job that's currently running that might produce a better payload.
+    maybe_better: Option<PendingPayload<Payload>>,
+    /// The empty payload building job in progress.
+    empty_payload: Option<oneshot::Receiver<Result<Payload, PayloadBuilderError>>>,
+    /// How long to wait for `maybe_better` before resolving the empty payload.
+    wait_for_pending: Option<Pin<Box<Sleep>>>,
+    /// Decides whether the result of `maybe_better` replaces the best payload.
+    checks: PayloadChecks,
+}
+
+impl<Payload> Future for ResolveBestPayload<Payload>
+where
+    Payload: BuiltPayload + Unpin,
+{
+    type Output = Result<Payload, PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // check if there is a better payload before returning the best payload
+        if let Some(fut) = Pin::new(&mut this.maybe_better).as_pin_mut() {
+            let dry_run = fut.dry_run;
+            if let Poll::Ready(res) = fut.poll(cx) {
+                this.maybe_better = None;
+                if let Ok(
+                    BuildOutcome::Better { payload, .. } | BuildOutcome::Empty { payload, .. },
+                ) = res
+                {
+                    match this.checks.check(&payload, this.best_payload.as_ref(), dry_run) {
+                        Ok(()) => {
+                            debug!(target: "payload_builder", "resolving better payload");
+                            return Poll::Ready(Ok(payload))
+                        }
+                        Err(rejection) => {
+                            debug!(target: "payload_builder", ?rejection, "discarded in progress payload on resolve");
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(best) = this.best_payload.take() {
+            debug!(target: "payload_builder", "resolving best payload");
+            return Poll::Ready(Ok(best))
+        }
+
+        // prefer the in progress build over the empty payload until the wait elapsed
+        if this.maybe_better.is_some() {
+            if let Some(wait) = this.wait_for_pending.as_mut() {
+                if wait.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending
+                }
+                this.wait_for_pending = None;
+            }
+        }
+
+        let mut empty_payload = this.empty_payload.take().expect("polled after completion");
+        match empty_payload.poll_unpin(cx) {
+            Poll::Ready(Ok(res)) => {
+                match &res {
+                    Ok(payload) => {
+                        let withdrawals =
+                            payload.block().withdrawals.as_ref().map_or(0, |w| w.len());
+                        debug!(target: "payload_builder", kind = ?payload.kind(), withdrawals, "resolving empty payload");
+                    }
+                    Err(err) => {
+                        warn!(target: "payload_builder", %err, "failed to resolve empty payload");
+                    }
+                }
+                Poll::Ready(res)
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err.into())),
+            Poll::Pending => {
+                this.empty_payload = Some(empty_payload);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A future that resolves to the result of the block building job.
+#[derive(Debug)]
+struct PendingPayload<P> {
+    /// The marker to cancel the job on drop
+    _cancel: Cancelled,
+    /// Whether the outcome must not be committed to the job.
+    dry_run: bool,
+    /// The approximate size of the cached reads the build started with.
+    cached_reads_size: usize,
+    /// When the build was spawned.
+    started: tokio::time::Instant,
+    /// Fires once the build took too long, see [BasicPayloadJobGeneratorConfig::build_timeout].
+    timeout: Option<Pin<Box<Sleep>>>,
+    /// The channel to send the result to.
+    payload: oneshot::Receiver<Result<BuildOutcome<P>, PayloadBuilderError>>,
+}
+
+impl<P> Future for PendingPayload<P> {
+    type Output = Result<BuildOutcome<P>, PayloadBuilderError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(res) = self.payload.poll_unpin(cx) {
+            return Poll::Ready(res.map_err(Into::into).and_then(|res| res))
+        }
+        if let Some(timeout) = self.timeout.as_mut() {
+            ready!(timeout.as_mut().poll(cx));
+            // dropping the pending payload cancels the build
+            let elapsed = self.started.elapsed();
+            return Poll::Ready(Err(PayloadBuilderError::BuildTimedOut { elapsed }))
+        }
+        Poll::Pending
+    }
+}
+
+/// A marker that can be used to cancel a job.
+///
+/// If dropped, it will set the `cancelled` flag to true.
+#[derive(Default, Clone, Debug)]
+pub struct Cancelled(Arc<AtomicBool>);
+
+// === impl Cancelled ===
+
+impl Cancelled {
+    /// Returns true if the job was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Static config for how to build a payload.
+#[derive(Clone, Debug)]
+pub struct PayloadConfig<Attributes> {
+    /// Pre-configured block environment.
+    pub initialized_block_env: BlockEnv,
+    /// Configuration for the environment.
+    pub initialized_cfg: CfgEnvWithHandlerCfg,
+    /// The parent block.
+    pub parent_block: Arc<SealedBlock>,
+    /// Block extra data.
+    pub extra_data: Bytes,
+    /// Requested attributes for the payload.
+    pub attributes: Attributes,
+    /// The chain spec.
+    pub chain_spec: Arc<ChainSpec>,
+    /// Transactions that must be included at the top of the block, in order.
+    ///
+    /// See [PayloadBuilder::try_build].
+    pub forced_top_of_block: Vec<TransactionSigned>,
+    /// Debug callback that builders invoke with the bundle state of the payload before computing
+    /// its state root, if set.
+    pub debug_bundle_state: Option<BundleStateHook>,
+    /// Maximum number of pool transactions builders include in the payload, if set.
+    ///
+    /// This doesn't limit the [PayloadConfig::forced_top_of_block] transactions.
+    pub max_transactions: Option<usize>,
+    /// How builders merge the state transitions of the payload into its bundle state.
+    ///
+    /// Defaults to [BundleRetention::PlainState], which discards the reverts.
+    pub bundle_retention: BundleRetention,
+    /// Debug callback that builders invoke with the call traces of every executed transaction, if
+    /// set.
+    ///
+    /// Tracing slows down building considerably, see [transact].
+    pub debug_tx_traces: Option<TxTraceHook>,
+    /// Whether empty payloads are built on the latest state if the state of the parent block isn't
+    /// available, see [parent_state_provider].
+    ///
+    /// This is only intended for development.
+    pub latest_state_fallback: bool,
+}
+
+impl<Attributes> PayloadConfig<Attributes> {
+    /// Returns an owned instance of the [PayloadConfig]'s extra_data bytes.
+    pub fn extra_data(&self) -> Bytes {
+        self.extra_data.clone()
+    }
+
+    /// Returns the config with the given extra_data, all other fields are unchanged.
+    ///
+    /// # Panics
+    ///
+    /// If `extra_data` exceeds [MAXIMUM_EXTRA_DATA_SIZE] bytes.
+    pub fn with_extra_data(mut self, extra_data: Bytes) -> Self {
+        assert!(
+            extra_data.len() <= MAXIMUM_EXTRA_DATA_SIZE,
+            "extra_data must not exceed {MAXIMUM_EXTRA_DATA_SIZE} bytes"
+        );
+        self.extra_data = extra_data;
+        self
+    }
+
+    /// Returns the number of the payload block.
+    ///
+    /// Fails with [PayloadBuilderError::BlockNumberMismatch] if the number of the block environment
+    /// isn't the number of the parent block plus one, which indicates an inconsistent provider or
+    /// cache.
+    pub fn block_number(&self) -> Result<u64, PayloadBuilderError> {
+        let expected = self.parent_block.number + 1;
+        let got = self.initialized_block_env.number.saturating_to::<u64>();
+        if got != expected {
+            return Err(PayloadBuilderError::BlockNumberMismatch { expected, got })
+        }
+        Ok(got)
+    }
+
+    /// Returns the transactions that must be included at the top of the block, in order.
+    pub fn forced_top_of_block(&self) -> &[TransactionSigned] {
+        &self.forced_top_of_block
+    }
+
+    /// Returns the config with the given transactions forced at the top of the block, all other
+    /// fields are unchanged.
+    pub fn with_forced_top_of_block(mut self, forced_top_of_block: Vec<TransactionSigned>) -> Self {
+        self.forced_top_of_block = forced_top_of_block;
+        self
+    }
+
+    /// Returns the config with the given bundle state debug callback, all other fields are
+    /// unchanged.
+    pub fn with_debug_bundle_state(mut self, hook: BundleStateHook) -> Self {
+        self.debug_bundle_state = Some(hook);
+        self
+    }
+
+    /// Returns the config with the given maximum number of pool transactions, all other fields are
+    /// unchanged.
+    pub fn with_max_transactions(mut self, max_transactions: usize) -> Self {
+        self.max_transactions = Some(max_transactions);
+        self
+    }
+
+    /// Returns the config with the given bundle retention, all other fields are unchanged.
+    ///
+    /// [BundleRetention::Reverts] keeps the reverts of the payload in the bundle state passed to
+    /// the [PayloadConfig::debug_bundle_state] callback.
+    pub fn with_bundle_retention(mut self, bundle_retention: BundleRetention) -> Self {
+        self.bundle_retention = bundle_retention;
+        self
+    }
+
+    /// Returns the config with the given transaction trace callback, all other fields are
+    /// unchanged.
+    pub fn with_debug_tx_traces(mut self, hook: TxTraceHook) -> Self {
+        self.debug_tx_traces = Some(hook);
+        self
+    }
+
+    /// Returns the config with the timestamp of the EVM block environment set to the given
+    /// timestamp, all other fields are unchanged.
+    ///
+    /// The header timestamp of built payloads is still taken from the attributes, so the payload
+    /// is invalid. This is only intended for testing time dependent contracts.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn with_block_env_timestamp_override(mut self, timestamp: u64) -> Self {
+        self.initialized_block_env.timestamp = U256::from(timestamp);
+        self
+    }
+
+    /// Returns the config with the given block gas limit, all other fields are unchanged.
+    ///
+    /// This overrides the gas limit derived from the parent block and the attributes. Builders use
+    /// the gas limit of the block environment for the header, so the built block is consistent.
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.initialized_block_env.gas_limit = U256::from(gas_limit);
+        self
+    }
+
+    /// Returns the config with the attributes mapped by the given function, all other fields are
+    /// unchanged.
+    ///
+    /// This allows builders with custom attributes to delegate to builders of the wrapped
+    /// attributes.
+    pub fn map_attributes<T>(self, f: impl FnOnce(Attributes) -> T) -> PayloadConfig<T> {
+        PayloadConfig {
+            initialized_block_env: self.initialized_block_env,
+            initialized_cfg: self.initialized_cfg,
+            parent_block: self.parent_block,
+            extra_data: self.extra_data,
+            attributes: f(self.attributes),
+            chain_spec: self.chain_spec,
+            forced_top_of_block: self.forced_top_of_block,
+            debug_bundle_state: self.debug_bundle_state,
+            max_transactions: self.max_transactions,
+            bundle_retention: self.bundle_retention,
+            debug_tx_traces: self.debug_tx_traces,
+            latest_state_fallback: self.latest_state_fallback,
+        }
+    }
+}
+
+impl<Attributes> PayloadConfig<Attributes>
+where
+    Attributes: PayloadBuilderAttributes,
+{
+    /// Create new payload config.
+    pub fn new(
+        parent_block: Arc<SealedBlock>,
+        extra_data: Bytes,
+        attributes: Attributes,
+        chain_spec: Arc<ChainSpec>,
+    ) -> Self {
+        // configure evm env based on parent block
+        let (initialized_cfg, initialized_block_env) =
+            attributes.cfg_and_block_env(&chain_spec, &parent_block);
+
+        Self {
+            initialized_block_env,
+            initialized_cfg,
+            parent_block,
+            extra_data,
+            attributes,
+            chain_spec,
+            forced_top_of_block: Vec::new(),
+            debug_bundle_state: None,
+            max_transactions: None,
+            bundle_retention: BundleRetention::PlainState,
+            debug_tx_traces: None,
+            latest_state_fallback: false,
+        }
+    }
+
+    /// Returns the payload id.
+    pub fn payload_id(&self) -> PayloadId {
+        self.attributes.payload_id()
+    }
+
+    /// Returns the timestamp of the payload's slot.
+    pub fn timestamp(&self) -> u64 {
+        self.attributes.timestamp()
+    }
+
+    /// Returns how much time passed since the payload's timestamp.
+    ///
+    /// Returns `Duration::ZERO` if the timestamp is in the future.
+    pub fn elapsed_in_slot(&self) -> Duration {
+        self.elapsed_in_slot_with(&SystemClock)
+    }
+
+    /// Returns how much time passed since the payload's timestamp according to the given clock.
+    ///
+    /// See also [PayloadConfig::elapsed_in_slot].
+    pub fn elapsed_in_slot_with(&self, clock: &dyn PayloadClock) -> Duration {
+        let now = clock.now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        now.saturating_sub(Duration::from_secs(self.timestamp()))
+    }
+}
+
+/// The possible outcomes of a payload building attempt.
+#[derive(Debug)]
+pub enum BuildOutcome<Payload> {
+    /// Successfully built a better block.
+    Better {
+        /// The new payload that was built.
+        payload: Payload,
+        /// The cached reads that were used to build the payload.
+        cached_reads: CachedReads,
+    },
+    /// Aborted payload building because resulted in worse block wrt. fees.
+    Aborted {
+        /// The total fees associated with the attempted payload.
+        fees: U256,
+        /// The cached reads that were used to build the payload.
+        cached_reads: CachedReads,
+    },
+    /// Successfully built a better block without transactions, because there were none to include.
+    ///
+    /// The job treats this like [BuildOutcome::Better], and additionally serves the payload as the
+    /// empty payload of its attributes, so it isn't built a second time.
+    Empty {
+        /// The new empty payload that was built.
+        payload: Payload,
+        /// The cached reads that were used to build the payload.
+        cached_reads: CachedReads,
+    },
+    /// Build job was cancelled
+    Cancelled,
+}
+
+/// A collection of arguments used for building payloads.
+///
+/// This struct encapsulates the essential components and configuration required for the payload
+/// building process. It holds references to the Ethereum client, transaction pool, cached reads,
+/// payload configuration, cancellation status, and the best payload achieved so far.
+#[derive(Debug)]
+pub struct BuildArguments<Pool, Client, Attributes, Payload> {
+    /// How to interact with the chain.
+    pub client: Client,
+    /// The transaction pool.
+    pub pool: Pool,
+    /// Previously cached disk reads
+    pub cached_reads: CachedReads,
+    /// How to configure the payload.
+    pub config: PayloadConfig<Attributes>,
+    /// A marker that can be used to cancel the job.
+    pub cancel: Cancelled,
+    /// The best payload achieved so far.
+    pub best_payload: Option<Payload>,
+}
+
+impl<Pool, Client, Attributes, Payload> BuildArguments<Pool, Client, Attributes, Payload> {
+    /// Create new build arguments.
+    pub fn new(
+        client: Client,
+        pool: Pool,
+        cached_reads: CachedReads,
+        config: PayloadConfig<Attributes>,
+        cancel: Cancelled,
+        best_payload: Option<Payload>,
+    ) -> Self {
+        Self { client, pool, cached_reads, config, cancel, best_payload }
+    }
+
+    /// Returns a builder for build arguments with the given client, pool and config.
+    ///
+    /// The remaining arguments default to empty cached reads, a fresh [Cancelled] marker and no
+    /// best payload.
+    pub fn builder(
+        client: Client,
+        pool: Pool,
+        config: PayloadConfig<Attributes>,
+    ) -> BuildArgumentsBuilder<Pool, Client, Attributes, Payload> {
+        BuildArgumentsBuilder {
+            args: Self::new(
+                client,
+                pool,
+                CachedReads::default(),
+                config,
+                Cancelled::default(),
+                None,
+            ),
+        }
+    }
+
+    /// Returns the gas that is left in the block after `used` gas has been consumed.
+    ///
+    /// Pool transactions with a gas limit above this must be skipped, see
+    /// [PayloadBuilder::try_build].
+    pub fn remaining_gas(&self, used: u64) -> u64 {
+        let gas_limit: u64 =
+            self.config.initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+        gas_limit.saturating_sub(used)
+    }
+}
+
+/// Builds [BuildArguments], see [BuildArguments::builder].
+#[derive(Debug)]
+pub struct BuildArgumentsBuilder<Pool, Client, Attributes, Payload> {
+    /// The arguments built so far.
+    args: BuildArguments<Pool, Client, Attributes, Payload>,
+}
+
+impl<Pool, Client, Attributes, Payload> BuildArgumentsBuilder<Pool, Client, Attributes, Payload> {
+    /// Sets the previously cached disk reads.
+    pub fn with_cached_reads(mut self, cached_reads: CachedReads) -> Self {
+        self.args.cached_reads = cached_reads;
+        self
+    }
+
+    /// Sets the marker that cancels the build.
+    pub fn with_cancel(mut self, cancel: Cancelled) -> Self {
+        self.args.cancel = cancel;
+        self
+    }
+
+    /// Sets the best payload achieved so far.
+    pub fn with_best_payload(mut self, best_payload: Option<Payload>) -> Self {
+        self.args.best_payload = best_payload;
+        self
+    }
+
+    /// Returns the build arguments.
+    pub fn build(self) -> BuildArguments<Pool, Client, Attributes, Payload> {
+        self.args
+    }
+}
+
+/// A trait for building payloads that encapsulate Ethereum transactions.
+///
+/// This trait provides the `try_build` method to construct a transaction payload
+/// using `BuildArguments`. It returns a `Result` indicating success or a
+/// `PayloadBuilderError` if building fails.
+///
+/// Generic parameters `Pool` and `Client` represent the transaction pool and
+/// Ethereum client types.
+pub trait PayloadBuilder<Pool, Client>: Send + Sync + Clone {
+    /// The payload attributes type to accept for building.
+    type Attributes: PayloadBuilderAttributes;
+    /// The type of the built payload.
+    type BuiltPayload: BuiltPayload;
+
+    /// Tries to build a transaction payload using provided arguments.
+    ///
+    /// Constructs a transaction payload based on the given arguments,
+    /// returning a `Result` indicating success or an error if building fails.
+    ///
+    /// The [PayloadConfig::forced_top_of_block] transactions must be included in order before any
+    /// transaction from the pool, and the gas they use is deducted from the block's gas budget.
+    ///
+    /// A pool transaction whose gas limit exceeds the [BuildArguments::remaining_gas] must be
+    /// skipped (and marked invalid for this build) instead of ending the build, so a single large
+    /// transaction at the front of the pool can't prevent the block from filling up.
+    ///
+    /// # Arguments
+    ///
+    /// - `args`: Build arguments containing necessary components.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating the build outcome or an error.
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError>;
+
+    /// Invoked when a new payload job is created, after the builder was cloned for the job.
+    ///
+    /// Builders that share state across clones can use this to reset per-job state. Unlike
+    /// [PayloadBuilder::on_job_start], this runs before the job is polled.
+    fn reset(&self, payload_id: PayloadId) {
+        let _payload_id = payload_id;
+    }
+
+    /// Invoked once when the payload job is first polled.
+    ///
+    /// This can be used to initialize per-job state before the first build attempt.
+    fn on_job_start(&self, config: &PayloadConfig<Self::Attributes>) {
+        let _config = config;
+    }
+
+    /// Invoked when a payload built by this builder replaces the best payload of the job.
+    ///
+    /// Dry run payloads never become the best payload, see [PayloadJobsHandle::request_dry_run].
+    fn on_better_payload(&self, payload: &Self::BuiltPayload) {
+        let _payload = payload;
+    }
+
+    /// Invoked when the payload job is being resolved and there is no payload yet.
+    ///
+    /// If this returns a payload, it will be used as the final payload for the job.
+    ///
+    /// TODO(mattsse): This needs to be refined a bit because this only exists for OP atm
+    fn on_missing_payload(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Option<Self::BuiltPayload> {
+        let _args = args;
+        None
+    }
+
+    /// Returns the accounts and storage slots the payload is expected to touch, if known.
+    ///
+    /// These are prewarmed into the job's [CachedReads] before the first build attempt.
+    fn access_list(&self, config: &PayloadConfig<Self::Attributes>) -> Option<AccessList> {
+        let _config = config;
+        None
+    }
+
+    /// Returns the hardforks this builder can build payloads for.
+    ///
+    /// The [BasicPayloadJobGenerator] warns about scheduled forks of the chain that aren't in this
+    /// list, before they activate. Defaults to `None`, which supports all forks.
+    fn supported_forks(&self) -> Option<&[Hardfork]> {
+        None
+    }
+
+    /// Returns a fingerprint of the transactions the next build would include from the pool.
+    ///
+    /// The job skips an interval if the fingerprint equals the one of its last build, for example
+    /// a hash of the sender, nonce and fee of the best transactions, unless transactions were
+    /// forced at the top of the block, see [PayloadJobsHandle::force_top_of_block]. Defaults to
+    /// `None`, which always rebuilds.
+    fn pool_fingerprint(&self, _pool: &Pool) -> Option<u64> {
+        None
+    }
+
+    /// Returns the block gas limit of the payload job with the given config, if it overrides the
+    /// gas limit derived from the parent block, see [PayloadConfig::with_gas_limit].
+    ///
+    /// The [BasicPayloadJobGenerator] still raises the gas limit to its
+    /// [minimum gas limit](BasicPayloadJobGeneratorConfig::min_gas_limit). Defaults to the gas
+    /// limit requested by the attributes, see [PayloadBuilderAttributes::gas_limit].
+    fn gas_limit(&self, config: &PayloadConfig<Self::Attributes>) -> Option<u64> {
+        config.attributes.gas_limit()
+    }
+
+    /// Builds an empty payload without any transaction.
+    fn build_empty_payload(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError>;
+
+    /// Builds an empty payload without any transaction, reading the parent state through the given
+    /// [CachedReads].
+    ///
+    /// The job passes the reads cached by its builds, so the empty payload doesn't read the same
+    /// state again, see [cached_payload_state]. Defaults to [PayloadBuilder::build_empty_payload],
+    /// which ignores the cached reads.
+    fn build_empty_payload_with_cached_reads(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+        cached_reads: &mut CachedReads,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        let _cached_reads = cached_reads;
+        Self::build_empty_payload(client, config)
+    }
+}
+
+/// Recovers the signers of the [PayloadConfig::forced_top_of_block] transactions.
+///
+/// Fails with [PayloadBuilderError::ForcedTransactionsExceedGasLimit] if the gas limits of the
+/// transactions exceed the gas that is left in the block, and with
+/// [PayloadBuilderError::InvalidForcedTransaction] if a transaction can't be included.
+pub fn recover_forced_top_of_block(
+    forced_top_of_block: Vec<TransactionSigned>,
+    remaining_gas: u64,
+) -> Result<Vec<TransactionSignedEcRecovered>, PayloadBuilderError> {
+    let gas_limit =
+        forced_top_of_block.iter().fold(0u64, |gas, tx| gas.saturating_add(tx.gas_limit()));
+    if gas_limit > remaining_gas {
+        return Err(PayloadBuilderError::ForcedTransactionsExceedGasLimit {
+            gas_limit,
+            remaining_gas,
+        })
+    }
+    forced_top_of_block
+        .into_iter()
+        .map(|tx| {
+            let hash = tx.hash();
+            if tx.is_eip4844() {
+                return Err(PayloadBuilderError::InvalidForcedTransaction(hash))
+            }
+            tx.into_ecrecovered().ok_or(PayloadBuilderError::InvalidForcedTransaction(hash))
+        })
+        .collect()
+}
+
+/// Represents the outcome of committing withdrawals to the runtime database and post state.
+/// Pre-shanghai these are `None` values.
+#[derive(Default, Debug)]
+pub struct WithdrawalsOutcome {
+    /// committed withdrawals, if any.
+    pub withdrawals: Option<Withdrawals>,
+    /// withdrawals root if any.
+    pub withdrawals_root: Option<B256>,
+}
+
+impl WithdrawalsOutcome {
+    /// No withdrawals pre shanghai
+    pub fn pre_shanghai() -> Self {
+        Self { withdrawals: None, withdrawals_root: None }
+    }
+
+    /// No withdrawals
+    pub fn empty() -> Self {
+        Self {
+            withdrawals: Some(Withdrawals::default()),
+            withdrawals_root: Some(EMPTY_WITHDRAWALS),
+        }
+    }
+
+    /// Returns the number of committed withdrawals, `0` pre shanghai.
+    pub fn withdrawal_count(&self) -> usize {
+        self.withdrawals.as_ref().map_or(0, |withdrawals| withdrawals.len())
+    }
+
+    /// Returns true if this is the outcome before shanghai, which has no withdrawals root.
+    pub fn is_pre_shanghai(&self) -> bool {
+        self.withdrawals.is_none() && self.withdrawals_root.is_none()
+    }
+
+    /// Returns true if no withdrawals were committed after shanghai.
+    ///
+    /// Unlike [WithdrawalsOutcome::is_pre_shanghai], the block has the [EMPTY_WITHDRAWALS] root.
+    pub fn is_empty(&self) -> bool {
+        self.withdrawals.as_ref().is_some_and(|withdrawals| withdrawals.is_empty())
+    }
+}
+
+/// Executes the withdrawals and commits them to the _runtime_ Database and BundleState.
+///
+/// Returns the withdrawals root.
+///
+/// Returns `None` values pre shanghai
+///
+/// Fails with [PayloadBuilderError::WithdrawalBalanceIncrement] if the balances can't be
+/// incremented.
+pub fn commit_withdrawals<DB: Database<Error = ProviderError>>(
+    db: &mut State<DB>,
+    chain_spec: &ChainSpec,
+    timestamp: u64,
+    withdrawals: Withdrawals,
+) -> Result<WithdrawalsOutcome, PayloadBuilderError> {
+    if !chain_spec.is_shanghai_active_at_timestamp(timestamp) {
+        return Ok(WithdrawalsOutcome::pre_shanghai())
+    }
+
+    trace!(target: "payload_builder", withdrawal_count = withdrawals.len(), "committing withdrawals");
+
+    if withdrawals.is_empty() {
+        return Ok(WithdrawalsOutcome::empty())
+    }
+
+    let balance_increments =
+        post_block_withdrawals_balance_increments(chain_spec, timestamp, &withdrawals);
+
+    db.increment_balances(balance_increments)
+        .map_err(PayloadBuilderError::WithdrawalBalanceIncrement)?;
+
+    let withdrawals_root = proofs::calculate_withdrawals_root(&withdrawals);
+
+    // calculate withdrawals root
+    Ok(WithdrawalsOutcome {
+        withdrawals: Some(withdrawals),
+        withdrawals_root: Some(withdrawals_root),
+    })
+}
+
+/// Returns the empty payload for the given config, from the cache or built on the blocking pool
+/// with the given [CachedReads].
+fn spawn_empty_payload<Client, Pool, Tasks, Builder>(
+    executor: &Tasks,
+    client: Client,
+    config: PayloadConfig<Builder::Attributes>,
+    mut cached_reads: CachedReads,
+    empty_payloads: &EmptyPayloadCache<Builder::BuiltPayload>,
+) -> oneshot::Receiver<Result<Builder::BuiltPayload, PayloadBuilderError>>
+where
+    Client: Send + Sync + 'static,
+    Pool: 'static,
+    Tasks: TaskSpawner,
+    Builder: PayloadBuilder<Pool, Client> + 'static,
+    Builder::BuiltPayload: Clone,
+{
+    let (tx, rx) = oneshot::channel();
+    let key = EmptyPayloadKey::new(&config);
+    if let Some(payload) = key.as_ref().and_then(|key| empty_payloads.get(key)) {
+        trace!(target: "payload_builder", id=%config.payload_id(), "resolving cached empty payload");
+        let _ = tx.send(Ok(payload));
+    } else {
+        let empty_payloads = empty_payloads.clone();
+        executor.spawn_blocking(Box::pin(async move {
+            let res =
+                Builder::build_empty_payload_with_cached_reads(&client, config, &mut cached_reads);
+            if let (Some(key), Ok(payload)) = (key, &res) {
+                empty_payloads.insert(key, payload.clone());
+            }
+            let _ = tx.send(res);
+        }));
+    }
+    rx
+}
+
+/// Returns the [State] database a payload is built with, with bundle updates enabled.
+///
+/// All reads from the given database are cached in the [CachedReads], so that subsequent builds on
+/// the same parent state can reuse them.
+pub fn cached_payload_state<DB: DatabaseRef>(
+    cached_reads: &mut CachedReads,
+    db: DB,
+) -> State<WrapDatabaseRef<CachedReadsDBRef<'_, DB>>> {
+    State::builder().with_database_ref(cached_reads.as_db(db)).with_bundle_update().build()
+}
+
+/// Returns the timestamp based forks of the chain that activate after `now` and aren't `supported`.
+fn unsupported_upcoming_forks(
+    chain_spec: &ChainSpec,
+    supported: &[Hardfork],
+    now: u64,
+) -> Vec<Hardfork> {
+    chain_spec
+        .forks_iter()
+        .filter(|(fork, condition)| {
+            condition.as_timestamp().is_some_and(|timestamp| timestamp > now) &&
+                !supported.contains(fork)
+        })
+        .map(|(fork, _)| fork)
+        .collect()
+}
+
+/// Returns the `excess_blob_gas` of a post-Cancun child of the given parent block.
+///
+/// For the first post-Cancun block, both `parent.excess_blob_gas` and `parent.blob_gas_used` are
+/// evaluated as 0, see [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844#header-extension).
+pub fn compute_excess_blob_gas(chain_spec: &ChainSpec, parent: &Header) -> u64 {
+    if chain_spec.is_cancun_active_at_timestamp(parent.timestamp) {
+        calculate_excess_blob_gas(
+            parent.excess_blob_gas.unwrap_or_default(),
+            parent.blob_gas_used.unwrap_or_default(),
+        )
+    } else {
+        calculate_excess_blob_gas(0, 0)
+    }
+}
+
+/// Apply the [EIP-4788](https://eips.ethereum.org/EIPS/eip-4788) pre block contract call.
+///
+/// This constructs a new [Evm] with the given DB, and environment
+/// ([CfgEnvWithHandlerCfg] and [BlockEnv]) to execute the pre block contract call.
+///
+/// The parent beacon block root used for the call is gathered from the given
+/// [PayloadBuilderAttributes].
+///
+/// This uses [apply_beacon_root_contract_call] to ultimately apply the beacon root contract state
+/// change.
+pub fn pre_block_beacon_root_contract_call<DB: Database + DatabaseCommit, Attributes>(
+    db: &mut DB,
+    chain_spec: &ChainSpec,
+    block_number: u64,
+    initialized_cfg: &CfgEnvWithHandlerCfg,
+    initialized_block_env: &BlockEnv,
+    attributes: &Attributes,
+) -> Result<(), PayloadBuilderError>
+where
+    DB::Error: std::fmt::Display,
+    Attributes: PayloadBuilderAttributes,
+{
+    // apply pre-block EIP-4788 contract call
+    let mut evm_pre_block = Evm::builder()
+        .with_db(db)
+        .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            Default::default(),
+        ))
+        .build();
+
+    // initialize a block from the env, because the pre block call needs the block itself
+    apply_beacon_root_contract_call(
+        chain_spec,
+        attributes.timestamp(),
+        block_number,
+        attributes.parent_beacon_block_root(),
+        &mut evm_pre_block,
+    )
+    .map_err(|err| PayloadBuilderError::Internal(err.into()))
+}
+
+/// Returns the best outcome of multiple candidate builds.
+///
+/// Better payloads are ranked by their fees. If no candidate produced a better payload, an aborted
+/// outcome is preferred over a cancelled one, so that its cached reads are retained.
+fn best_candidate_outcome<P: BuiltPayload>(
+    outcomes: impl IntoIterator<Item = Result<BuildOutcome<P>, PayloadBuilderError>>,
+) -> Result<BuildOutcome<P>, PayloadBuilderError> {
+    fn rank<P: BuiltPayload>(outcome: &Result<BuildOutcome<P>, PayloadBuilderError>) -> (u8, U256) {
+        match outcome {
+            Ok(BuildOutcome::Better { payload, .. } | BuildOutcome::Empty { payload, .. }) => {
+                (3, payload.fees())
+            }
+            Ok(BuildOutcome::Aborted { .. }) => (2, U256::ZERO),
+            Ok(BuildOutcome::Cancelled) => (1, U256::ZERO),
+            Err(_) => (0, U256::ZERO),
+        }
+    }
+
+    let mut best = None;
+    for outcome in outcomes {
+        best = match best {
+            Some(current) if rank(&current) >= rank(&outcome) => Some(current),
+            _ => Some(outcome),
+        };
+    }
+    best.unwrap_or(Ok(BuildOutcome::Cancelled))
+}
+
+/// Returns the state of the given parent block.
+///
+/// If `latest_state_fallback` is set and the state of the parent block isn't available, for example
+/// during a reorg, this falls back to the latest state if the parent block is a canonical ancestor
+/// of the latest block. The returned state is then only an approximation of the parent state.
+pub fn parent_state_provider<Client: StateProviderFactory>(
+    client: &Client,
+    parent_block: &SealedBlock,
+    latest_state_fallback: bool,
+) -> Result<StateProviderBox, ProviderError> {
+    match client.state_by_block_hash(parent_block.hash()) {
+        Err(ProviderError::StateForHashNotFound(hash)) if latest_state_fallback => {
+            latest_state_of_ancestor(client, parent_block)?
+                .ok_or(ProviderError::StateForHashNotFound(hash))
+        }
+        res => res,
+    }
+}
+
+/// Returns the latest state if the given block is a canonical ancestor of the latest block.
+fn latest_state_of_ancestor<Client: StateProviderFactory>(
+    client: &Client,
+    block: &SealedBlock,
+) -> Result<Option<StateProviderBox>, ProviderError> {
+    let best_number = client.best_block_number()?;
+    if block.number > best_number || client.block_hash(block.number)? != Some(block.hash()) {
+        return Ok(None)
+    }
+    warn!(target: "payload_builder", parent_hash = %block.hash(), parent_number = block.number, best_number, "state of parent block not found, approximating it with the latest state");
+    client.latest().map(Some)
+}
+
+/// Prewarms the cached reads with the state of the access list at the given block.
+///
+/// Failing to prewarm is not fatal, the state is then read during the build instead.
+fn prewarm_cached_reads<Client: StateProviderFactory>(
+    client: &Client,
+    block: B256,
+    cached_reads: &mut CachedReads,
+    access_list: AccessList,
+) {
+    let accounts: Vec<_> = access_list.0.iter().map(|item| item.address).collect();
+    let storage: Vec<_> = access_list
+        .0
+        .iter()
+        .flat_map(|item| item.storage_keys.iter().map(|key| (item.address, *key)))
+        .collect();
+
+    let res = client
+        .state_by_block_hash(block)
+        .and_then(|state| cached_reads.prewarm(state, &accounts, &storage));
+    match res {
+        Ok(()) => {
+            trace!(target: "payload_builder", accounts = accounts.len(), slots = storage.len(), "prewarmed cached reads")
+        }
+        Err(err) => debug!(target: "payload_builder", %err, "failed to prewarm cached reads"),
+    }
+}
+
+/// Checks if the new payload is better than the current best.
+///
+/// This compares the total fees of the blocks, higher is better.
+#[inline(always)]
+pub fn is_better_payload(best_payload: Option<impl BuiltPayload>, new_fees: U256) -> bool {
+    if let Some(best_payload) = best_payload {
+        new_fees > best_payload.fees()
+    } else {
+        true
+    }
+}
+
+/// Decides whether a newly built payload replaces the best payload of a [BasicPayloadJob].
+///
+/// See [BasicPayloadJobGeneratorConfig::ranking].
+pub trait PayloadRanking: fmt::Debug + Send + Sync {
+    /// Returns true if a payload with the given fees is better than the current best payload.
+    fn is_better(&self, current: Option<&dyn BuiltPayload>, candidate_fees: U256) -> bool;
+}
+
+/// The default [PayloadRanking], the payload with the higher total fees is better.
+///
+/// See [is_better_payload].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FeeRanking;
+
+impl PayloadRanking for FeeRanking {
+    fn is_better(&self, current: Option<&dyn BuiltPayload>, candidate_fees: U256) -> bool {
+        current.map_or(true, |current| candidate_fees > current.fees())
+    }
+}
+
+/// The checks a built payload must pass to replace the best payload of a job.
+///
+/// Shared by the job and the [ResolveBestPayload] future, so an in progress build that finishes
+/// on resolve is held to the same rules.
+#[derive(Debug, Clone)]
+struct PayloadChecks {
+    /// Hash of the block the payloads must build on.
+    parent_hash: B256,
+    /// See [BasicPayloadJobGeneratorConfig::max_encoded_block_size].
+    max_encoded_block_size: Option<usize>,
+    /// See [BasicPayloadJobGeneratorConfig::ranking].
+    ranking: Arc<dyn PayloadRanking>,
+}
+
+// === impl PayloadChecks ===
+
+impl PayloadChecks {
+    /// Returns `Ok` if the payload replaces the given best payload.
+    ///
+    /// Dry run payloads never replace the best payload, see [PayloadJobsHandle::request_dry_run].
+    fn check<P: BuiltPayload>(
+        &self,
+        payload: &P,
+        best_payload: Option<&P>,
+        dry_run: bool,
+    ) -> Result<(), PayloadRejection> {
+        if payload.block().parent_hash != self.parent_hash {
+            return Err(PayloadRejection::WrongParent)
+        }
+        if dry_run {
+            return Err(PayloadRejection::DryRun)
+        }
+        if best_payload.is_some_and(|best| best.block().hash() == payload.block().hash()) {
+            return Err(PayloadRejection::Identical)
+        }
+        if self.max_encoded_block_size.is_some_and(|max| payload.block().length() > max) {
+            return Err(PayloadRejection::Oversized)
+        }
+        if !self
+            .ranking
+            .is_better(best_payload.map(|best| best as &dyn BuiltPayload), payload.fees())
+        {
+            return Err(PayloadRejection::NotRankedBetter)
+        }
+        Ok(())
+    }
+}
+
+/// Why a built payload doesn't replace the best payload, see [PayloadChecks::check].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PayloadRejection {
+    /// The payload doesn't build on the parent of the job.
+    WrongParent,
+    /// The payload was built by a dry run.
+    DryRun,
+    /// The payload is the best payload.
+    Identical,
+    /// The payload exceeds the maximum encoded block size.
+    Oversized,
+    /// The payload isn't ranked better than the best payload.
+    NotRankedBetter,
+}
+
+// === impl PayloadRejection ===
+
+impl PayloadRejection {
+    /// Returns true if the build is still committed to the job, which keeps its cached reads.
+    const fn is_committed(self) -> bool {
+        !matches!(self, Self::WrongParent | Self::DryRun)
+    }
+}
+
+/// Executes the transaction of the given environment, without committing its state changes.
+///
+/// If a [TxTraceHook] is given, the execution is traced and the hook is invoked with the traces of
+/// the transaction with the given hash, see [PayloadConfig::debug_tx_traces].
+pub fn transact<DB: Database>(
+    db: DB,
+    env: EnvWithHandlerCfg,
+    tx_hash: TxHash,
+    hook: Option<&TxTraceHook>,
+) -> Result<ResultAndState, EVMError<DB::Error>> {
+    match hook {
+        Some(hook) => tx_tracing::transact_traced(db, env, tx_hash, hook),
+        None => Evm::builder().with_db(db).with_env_with_handler_cfg(env).build().transact(),
+    }
+}
+
+/// Returns the duration from `now` until the given unix timestamp in seconds.
+///
+/// Returns `Duration::ZERO` if the given timestamp is in the past.
+fn duration_until(now: SystemTime, unix_timestamp_secs: u64) -> Duration {
+    let unix_now = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let timestamp = Duration::from_secs(unix_timestamp_secs);
+    timestamp.saturating_sub(unix_now)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+    use reth_interfaces::db::{DatabaseError, DatabaseErrorInfo};
+    use reth_node_api::{
+        validate_version_specific_fields, EngineApiMessageVersion, EngineObjectValidationError,
+        EngineTypes, PayloadOrAttributes,
+    };
+    use reth_payload_builder::{
+        EthBuiltPayload, EthPayloadBuilderAttributes, PayloadBuilderService, PayloadStore,
+    };
+    use reth_primitives::{
+        constants::eip4844::{MAX_DATA_GAS_PER_BLOCK, TARGET_DATA_GAS_PER_BLOCK},
+        Address, Block, ChainSpecBuilder, ForkCondition, Hardfork, Signature, Transaction,
+        TxLegacy, Withdrawal, MAINNET,
+    };
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_rpc_types::{
+        engine::{ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3, PayloadAttributes},
+        ExecutionPayloadV1,
+    };
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::noop::NoopTransactionPool;
+    use revm::primitives::{AccountInfo, Bytecode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub(crate) type MockGenerator<B = MockPayloadBuilder> =
+        BasicPayloadJobGenerator<MockEthProvider, NoopTransactionPool, TokioTaskExecutor, B>;
+
+    type MockJob<B = MockPayloadBuilder> =
+        BasicPayloadJob<MockEthProvider, NoopTransactionPool, TokioTaskExecutor, B>;
+
+    /// The outcome of a [MockPayloadBuilder] build attempt.
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) enum MockOutcome {
+        /// A better payload with the given fees.
+        Better(u64),
+        /// An aborted build.
+        Aborted,
+        /// A cancelled build.
+        Cancelled,
+        /// A failed build.
+        Failed,
+        /// A build that timed out.
+        TimedOut,
+        /// A build whose withdrawals can't be applied to the state.
+        WithdrawalsFailed,
+        /// A better payload that doesn't build on the configured parent.
+        WrongParent,
+        /// An empty payload, because there were no transactions to include.
+        Empty,
+    }
+
+    /// A [PayloadBuilder] that builds a child block of the configured parent, which only contains
+    /// the forced top of block transactions, and records how often it was invoked.
+    ///
+    /// Every better build caches an account whose address ends with the attempt number.
+    #[derive(Debug, Clone)]
+    pub(crate) struct MockPayloadBuilder {
+        builds: Arc<AtomicUsize>,
+        /// Outcomes of consecutive build attempts, the last one is repeated.
+        outcomes: Arc<Vec<MockOutcome>>,
+        /// Ids of the jobs that were started.
+        started: Arc<std::sync::Mutex<Vec<PayloadId>>>,
+        /// Ids the builder was reset with.
+        resets: Arc<std::sync::Mutex<Vec<PayloadId>>>,
+        /// The fingerprint of the pool, see [PayloadBuilder::pool_fingerprint].
+        fingerprint: Option<u64>,
+        /// The gas limit of every job, see [PayloadBuilder::gas_limit].
+        gas_limit: Option<u64>,
+        /// Fees of the payloads that became the best payload of a job.
+        better: Arc<std::sync::Mutex<Vec<U256>>>,
+    }
+
+    impl MockPayloadBuilder {
+        pub(crate) fn with_outcomes(outcomes: impl IntoIterator<Item = MockOutcome>) -> Self {
+            Self {
+                builds: Default::default(),
+                outcomes: Arc::new(outcomes.into_iter().collect()),
+                started: Default::default(),
+                resets: Default::default(),
+                fingerprint: None,
+                gas_limit: None,
+                better: Default::default(),
+            }
+        }
+
+        fn with_fingerprint(mut self, fingerprint: u64) -> Self {
+            self.fingerprint = Some(fingerprint);
+            self
+        }
+
+        fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+            self.gas_limit = Some(gas_limit);
+            self
+        }
+
+        pub(crate) fn builds(&self) -> usize {
+            self.builds.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Default for MockPayloadBuilder {
+        fn default() -> Self {
+            Self::with_outcomes([MockOutcome::Better(0)])
+        }
+    }
+
+    impl<Pool, Client> PayloadBuilder<Pool, Client> for MockPayloadBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn reset(&self, payload_id: PayloadId) {
+            self.resets.lock().unwrap().push(payload_id);
+        }
+
+        fn on_job_start(&self, config: &PayloadConfig<Self::Attributes>) {
+            self.started.lock().unwrap().push(config.payload_id());
+        }
+
+        fn pool_fingerprint(&self, _pool: &Pool) -> Option<u64> {
+            self.fingerprint
+        }
+
+        fn gas_limit(&self, _config: &PayloadConfig<Self::Attributes>) -> Option<u64> {
+            self.gas_limit
+        }
+
+        fn on_better_payload(&self, payload: &Self::BuiltPayload) {
+            self.better.lock().unwrap().push(payload.fees());
+        }
+
+        fn try_build(
+            &self,
+            args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+        ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+            let attempt = self.builds.fetch_add(1, Ordering::Relaxed);
+            let outcome = self.outcomes.get(attempt).or(self.outcomes.last()).copied();
+            Ok(match outcome.expect("no outcomes configured") {
+                MockOutcome::Better(fees) => {
+                    let mut cached_reads = args.cached_reads;
+                    cached_reads.insert_account(
+                        Address::with_last_byte(attempt as u8),
+                        AccountInfo::default(),
+                        Default::default(),
+                    );
+                    BuildOutcome::Better {
+                        payload: mock_payload(&args.config, U256::from(fees)),
+                        cached_reads,
+                    }
+                }
+                MockOutcome::Aborted => {
+                    BuildOutcome::Aborted { fees: U256::ZERO, cached_reads: args.cached_reads }
+                }
+                MockOutcome::Cancelled => BuildOutcome::Cancelled,
+                MockOutcome::Empty => BuildOutcome::Empty {
+                    payload: mock_payload(&args.config, U256::ZERO),
+                    cached_reads: args.cached_reads,
+                },
+                MockOutcome::WrongParent => {
+                    let mut payload = mock_payload(&args.config, U256::from(1));
+                    let mut block = payload.block().clone().unseal();
+                    block.header.parent_hash = B256::random();
+                    payload = EthBuiltPayload::new(payload.id(), block.seal_slow(), payload.fees());
+                    BuildOutcome::Better { payload, cached_reads: args.cached_reads }
+                }
+                MockOutcome::Failed => {
+                    return Err(PayloadBuilderError::MissingParentBlock(B256::ZERO))
+                }
+                MockOutcome::TimedOut => {
+                    return Err(PayloadBuilderError::BuildTimedOut {
+                        elapsed: Duration::from_secs(1),
+                    })
+                }
+                MockOutcome::WithdrawalsFailed => {
+                    let mut db = State::builder().with_database(FailingDatabase).build();
+                    let timestamp = MAINNET.fork(Hardfork::Shanghai).as_timestamp().unwrap();
+                    let withdrawals =
+                        Withdrawals::new(vec![Withdrawal { amount: 1, ..Default::default() }]);
+                    commit_withdrawals(&mut db, &MAINNET, timestamp, withdrawals)?;
+                    unreachable!("withdrawals can't be applied to a failing database")
+                }
+            })
+        }
+
+        fn build_empty_payload(
+            _client: &Client,
+            config: PayloadConfig<Self::Attributes>,
+        ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+            EMPTY_BUILDS.with(|builds| builds.set(builds.get() + 1));
+            Ok(mock_payload(&config, U256::ZERO))
+        }
+    }
+
+    /// A [PayloadBuilder] whose builds only finish once they are cancelled.
+    #[derive(Debug, Clone, Default)]
+    struct UntilCancelledBuilder {
+        /// Whether a build observed its cancellation.
+        cancelled: Arc<AtomicBool>,
+    }
+
+    impl<Pool, Client> PayloadBuilder<Pool, Client> for UntilCancelledBuilder {
+        type Attributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+
+        fn try_build(
+            &self,
+            args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+        ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+            while !args.cancel.is_cancelled() {
+                std::thread::yield_now();
+            }
+            self.cancelled.store(true, Ordering::Relaxed);
+            Ok(BuildOutcome::Cancelled)
+        }
+
+        fn build_empty_payload(
+            _client: &Client,
+            config: PayloadConfig<Self::Attributes>,
+        ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+            Ok(mock_payload(&config, U256::ZERO))
+        }
+    }
+
+    /// A [Database] that fails every read.
+    struct FailingDatabase;
+
+    impl Database for FailingDatabase {
+        type Error = ProviderError;
+
+        fn basic(&mut self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            Err(failing_database_error())
+        }
+
+        fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            Err(failing_database_error())
+        }
+
+        fn storage(&mut self, _address: Address, _index: U256) -> Result<U256, Self::Error> {
+            Err(failing_database_error())
+        }
+
+        fn block_hash(&mut self, _number: U256) -> Result<B256, Self::Error> {
+            Err(failing_database_error())
+        }
+    }
+
+    fn failing_database_error() -> ProviderError {
+        ProviderError::Database(DatabaseError::Read(DatabaseErrorInfo {
+            message: "corrupted".to_string(),
+            code: -1,
+        }))
+    }
+
+    thread_local! {
+        /// Number of empty payloads built by [MockPayloadBuilder] on the current thread.
+        static EMPTY_BUILDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    fn mock_payload(
+        config: &PayloadConfig<EthPayloadBuilderAttributes>,
+        fees: U256,
+    ) -> EthBuiltPayload {
+        let header = Header {
+            parent_hash: config.parent_block.hash(),
+            number: config.parent_block.number + 1,
+            timestamp: config.attributes.timestamp,
+            gas_limit: config.initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX),
+            // payloads with different fees are different blocks, so they aren't skipped as
+            // identical to the best payload
+            gas_used: fees.saturating_to(),
+            extra_data: config.extra_data(),
+            ..Default::default()
+        };
+        let block = Block {
+            header,
+            body: config.forced_top_of_block.clone(),
+            withdrawals: Some(config.attributes.withdrawals.clone()),
+            ..Default::default()
+        };
+        EthBuiltPayload::new(config.payload_id(), block.seal_slow(), fees)
+    }
+
+    /// Returns a generator whose client knows a single parent block.
+    pub(crate) fn mock_generator<B>(
+        builder: B,
+        config: BasicPayloadJobGeneratorConfig,
+    ) -> (MockGenerator<B>, SealedBlock)
+    where
+        B: PayloadBuilder<NoopTransactionPool, MockEthProvider>,
+    {
+        let client = MockEthProvider::default();
+        let parent =
+            Block { header: Header { number: 1, ..Default::default() }, ..Default::default() }
+                .seal_slow();
+        client.add_block(parent.hash(), parent.clone().unseal());
+        (mock_generator_with_client(client, builder, config), parent)
+    }
+
+    fn mock_generator_with_client<B>(
+        client: MockEthProvider,
+        builder: B,
+        config: BasicPayloadJobGeneratorConfig,
+    ) -> MockGenerator<B>
+    where
+        B: PayloadBuilder<NoopTransactionPool, MockEthProvider>,
+    {
+        BasicPayloadJobGenerator::with_builder(
+            client,
+            NoopTransactionPool::default(),
+            TokioTaskExecutor::default(),
+            config,
+            MAINNET.clone(),
+            builder,
+        )
+    }
+
+    pub(crate) fn mock_attributes(parent: B256) -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root: None,
+        }
+    }
+
+    fn mock_config() -> PayloadConfig<EthPayloadBuilderAttributes> {
+        let parent =
+            Block { header: Header { number: 1, ..Default::default() }, ..Default::default() }
+                .seal_slow();
+        let attributes = mock_attributes(parent.hash());
+        PayloadConfig::new(Arc::new(parent), Bytes::default(), attributes, MAINNET.clone())
+    }
+
+    /// Returns the [PayloadChecks] of a job with the given config and the default settings.
+    fn mock_checks(config: &PayloadConfig<EthPayloadBuilderAttributes>) -> PayloadChecks {
+        PayloadChecks {
+            parent_hash: config.parent_block.hash(),
+            max_encoded_block_size: None,
+            ranking: Arc::new(FeeRanking),
+        }
+    }
+
+    /// Polls the future once with a noop waker.
+    pub(crate) fn poll_once<F: Future + Unpin>(fut: &mut F) -> Poll<F::Output> {
+        let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+        Pin::new(fut).poll(&mut cx)
+    }
+
+    /// A waker that records whether it was woken.
+    #[derive(Debug, Default)]
+    struct WakeFlag(AtomicBool);
+
+    impl futures_util::task::ArcWake for WakeFlag {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Waits until the builder was invoked at least `n` times.
+    async fn wait_for_builds(builder: &MockPayloadBuilder, n: usize) {
+        while builder.builds() < n {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Spawns a new build and polls the job until the build finished.
+    pub(crate) async fn run_build<Pool, B>(
+        job: &mut BasicPayloadJob<MockEthProvider, Pool, TokioTaskExecutor, B>,
+    ) where
+        B: PayloadBuilder<Pool, MockEthProvider>,
+        BasicPayloadJob<MockEthProvider, Pool, TokioTaskExecutor, B>: Future + Unpin,
+    {
+        assert!(poll_once(job).is_pending());
+        assert!(job.pending_block.is_some(), "no build spawned");
+        while job.pending_block.is_some() {
+            tokio::task::yield_now().await;
+            assert!(poll_once(job).is_pending());
+        }
+    }
+
+    /// Spawns builds every interval until the job gives up, see [MAX_FATAL_BUILD_FAILURES].
+    async fn run_until_given_up(job: &mut MockJob, interval: Duration) -> PayloadBuilderError {
+        loop {
+            match poll_once(job) {
+                Poll::Ready(res) => return res.unwrap_err(),
+                Poll::Pending if job.pending_block.is_none() => {
+                    tokio::time::advance(interval).await
+                }
+                Poll::Pending => tokio::task::yield_now().await,
+            }
+        }
+    }
+
+    /// Returns the value of the counter recorded on the current thread.
+    fn counter(name: &str) -> u64 {
+        Snapshotter::current_thread_snapshot()
+            .into_iter()
+            .flat_map(|snapshot| snapshot.into_vec())
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Counter(value) if key.key().name() == name => Some(value),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    fn gauge(name: &str) -> Option<f64> {
+        Snapshotter::current_thread_snapshot()
+            .into_iter()
+            .flat_map(|snapshot| snapshot.into_vec())
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Gauge(value) if key.key().name() == name => Some(value.into_inner()),
+                _ => None,
+            })
+    }
+
+    fn histogram(name: &str) -> Vec<f64> {
+        Snapshotter::current_thread_snapshot()
+            .into_iter()
+            .flat_map(|snapshot| snapshot.into_vec())
+            .find_map(|(key, _, _, value)| match value {
+                DebugValue::Histogram(values) if key.key().name() == name => {
+                    Some(values.into_iter().map(|value| value.into_inner()).collect())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paused_job_skips_builds() {
+        let builder = MockPayloadBuilder::default();
+        let interval = Duration::from_secs(1);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let jobs = generator.jobs_handle();
+        let attributes = mock_attributes(parent.hash());
+        let id = attributes.payload_id();
+        assert!(jobs.pause_handle(id).is_none());
+
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        let pause = jobs.pause_handle(id).unwrap();
+        pause.pause();
+        assert!(pause.is_paused());
+        // other jobs aren't paused
+        let other = EthPayloadBuilderAttributes {
+            id: PayloadId::new([2; 8]),
+            ..job.config.attributes.clone()
+        };
+        let other_job = generator.new_payload_job(other).unwrap();
+        assert!(!other_job.control.is_paused());
+
+        for _ in 0..3 {
+            assert!(poll_once(&mut job).is_pending());
+            tokio::time::advance(interval).await;
+        }
+        assert!(job.pending_block.is_none());
+        assert_eq!(builder.builds(), 0);
+
+        pause.resume();
+        assert!(poll_once(&mut job).is_pending());
+        wait_for_builds(&builder, 1).await;
+    }
+
+    #[test]
+    fn missing_parent_block() {
+        let generator = mock_generator_with_client(
+            MockEthProvider::default(),
+            MockPayloadBuilder::default(),
+            Default::default(),
+        );
+        let parent = B256::random();
+        let err = generator.new_payload_job(mock_attributes(parent)).unwrap_err();
+        assert!(matches!(err, PayloadBuilderError::MissingParentBlock(hash) if hash == parent));
+    }
+
+    #[test]
+    fn missing_latest_block() {
+        // the client knows the latest header but not its block
+        let client = MockEthProvider::default();
+        client.add_header(B256::random(), Header { number: 1, ..Default::default() });
+        let generator =
+            mock_generator_with_client(client, MockPayloadBuilder::default(), Default::default());
+        let err = generator.new_payload_job(mock_attributes(B256::ZERO)).unwrap_err();
+        assert!(matches!(err, PayloadBuilderError::MissingLatestBlock));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn aborted_and_cancelled_build_metrics() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Aborted, MockOutcome::Cancelled]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert_eq!(counter("payloads.builds_aborted_worse_total"), 1);
+        assert_eq!(counter("payloads.builds_cancelled_total"), 0);
+
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(counter("payloads.builds_aborted_worse_total"), 1);
+        assert_eq!(counter("payloads.builds_cancelled_total"), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dry_run_does_not_commit_outcome() {
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Better(2)]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let jobs = generator.jobs_handle();
+        let attributes = mock_attributes(parent.hash());
+        let id = attributes.payload_id();
+        assert!(!jobs.request_dry_run(id));
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        run_build(&mut job).await;
+        assert_eq!(job.best_payload.as_ref().unwrap().fees(), U256::from(1));
+
+        assert!(jobs.request_dry_run(id));
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(job.best_payload.as_ref().unwrap().fees(), U256::from(1));
+
+        let mut cached_reads = job.cached_reads.take().unwrap();
+        let db = cached_reads.as_db(revm::db::EmptyDB::default());
+        assert!(db.basic_ref(Address::with_last_byte(0)).unwrap().is_some());
+        assert!(db.basic_ref(Address::with_last_byte(1)).unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn eager_first_build_on_job_creation() {
+        let builder = MockPayloadBuilder::default();
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().eager_first_build(true),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert!(job.pending_block.is_some());
+        assert_eq!(*builder.started.lock().unwrap(), vec![PayloadId::new([1; 8])]);
+        wait_for_builds(&builder, 1).await;
+
+        // the first poll doesn't spawn another build
+        while job.pending_block.is_some() {
+            assert!(poll_once(&mut job).is_pending());
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(builder.builds(), 1);
+        assert_eq!(builder.started.lock().unwrap().len(), 1);
+        assert!(job.best_payload.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn builder_is_reset_for_new_job() {
+        let builder = MockPayloadBuilder::default();
+        let (generator, parent) =
+            mock_generator(builder.clone(), BasicPayloadJobGeneratorConfig::default());
+        let attributes = mock_attributes(parent.hash());
+        let _job = generator.new_payload_job(attributes.clone()).unwrap();
+
+        assert_eq!(*builder.resets.lock().unwrap(), vec![attributes.id]);
+        assert!(builder.started.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn job_start_hook_runs_once() {
+        let builder = MockPayloadBuilder::default();
+        let (generator, parent) =
+            mock_generator(builder.clone(), BasicPayloadJobGeneratorConfig::default());
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert!(builder.started.lock().unwrap().is_empty());
+
+        for _ in 0..3 {
+            assert!(poll_once(&mut job).is_pending());
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(*builder.started.lock().unwrap(), vec![PayloadId::new([1; 8])]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resolve_waits_for_pending_build() {
+        let config = mock_config();
+        let (tx, rx) = oneshot::channel();
+        let (empty_tx, empty_rx) = oneshot::channel();
+        let mut fut = ResolveBestPayload {
+            best_payload: None,
+            maybe_better: Some(PendingPayload {
+                _cancel: Cancelled::default(),
+                dry_run: false,
+                cached_reads_size: 0,
+                started: tokio::time::Instant::now(),
+                timeout: None,
+                payload: rx,
+            }),
+            empty_payload: Some(empty_rx),
+            wait_for_pending: Some(Box::pin(tokio::time::sleep(Duration::from_secs(1)))),
+            checks: mock_checks(&config),
+        };
+        empty_tx.send(Ok(mock_payload(&config, U256::ZERO))).unwrap();
+        assert!(poll_once(&mut fut).is_pending());
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        let payload = mock_payload(&config, U256::from(5));
+        tx.send(Ok(BuildOutcome::Better { payload, cached_reads: Default::default() })).unwrap();
+        assert_eq!(fut.await.unwrap().fees(), U256::from(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resolve_checks_pending_build() {
+        let config = mock_config();
+        let resolve = |outcome: BuildOutcome<EthBuiltPayload>| {
+            let (tx, rx) = oneshot::channel();
+            tx.send(Ok(outcome)).unwrap();
+            ResolveBestPayload {
+                best_payload: Some(mock_payload(&config, U256::from(5))),
+                maybe_better: Some(PendingPayload {
+                    _cancel: Cancelled::default(),
+                    dry_run: false,
+                    cached_reads_size: 0,
+                    started: tokio::time::Instant::now(),
+                    timeout: None,
+                    payload: rx,
+                }),
+                empty_payload: None,
+                wait_for_pending: None,
+                checks: mock_checks(&config),
+            }
+        };
+
+        // a better pending payload replaces the best payload
+        let payload = mock_payload(&config, U256::from(7));
+        let fut = resolve(BuildOutcome::Better { payload, cached_reads: Default::default() });
+        assert_eq!(fut.await.unwrap().fees(), U256::from(7));
+
+        // a worse pending payload doesn't
+        let payload = mock_payload(&config, U256::from(3));
+        let fut = resolve(BuildOutcome::Better { payload, cached_reads: Default::default() });
+        assert_eq!(fut.await.unwrap().fees(), U256::from(5));
+
+        // neither does a pending payload built on the wrong parent
+        let mut wrong_parent = config.clone();
+        wrong_parent.parent_block = Arc::new(
+            Block { header: Header { number: 7, ..Default::default() }, ..Default::default() }
+                .seal_slow(),
+        );
+        let payload = mock_payload(&wrong_parent, U256::from(7));
+        let fut = resolve(BuildOutcome::Better { payload, cached_reads: Default::default() });
+        assert_eq!(fut.await.unwrap().fees(), U256::from(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn force_top_of_block_through_jobs_handle() {
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Better(2)]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let jobs = generator.jobs_handle();
+        let attributes = mock_attributes(parent.hash());
+        let payload_id = attributes.payload_id();
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        run_build(&mut job).await;
+        assert!(job.best_payload.as_ref().unwrap().block().body.is_empty());
+
+        let forced: Vec<_> = (0..2)
+            .map(|nonce| {
+                TransactionSigned::from_transaction_and_signature(
+                    Transaction::Legacy(TxLegacy { nonce, ..Default::default() }),
+                    Signature::default(),
+                )
+            })
+            .collect();
+        assert!(jobs.force_top_of_block(payload_id, forced.clone()));
+        assert!(!jobs.force_top_of_block(PayloadId::new([2; 8]), forced.clone()));
+
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(job.config().forced_top_of_block(), forced.as_slice());
+        assert_eq!(job.best_payload.as_ref().unwrap().block().body, forced);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn payload_tx_count_metric() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        job.config.forced_top_of_block = vec![TransactionSigned::default(); 3];
+
+        run_build(&mut job).await;
+        assert_eq!(histogram("payloads.payload_tx_count"), vec![3.0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn identical_better_payload_is_not_stored() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        // the mock builds the same block for both attempts
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Better(1)]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+
+        assert_eq!(builder.builds(), 2);
+        assert_eq!(counter("payloads.redundant_better_total"), 1);
+        assert_eq!(histogram("payloads.payload_tx_count").len(), 1);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn custom_ranking_keeps_best_payload() {
+        /// Keeps the first built payload.
+        #[derive(Debug)]
+        struct FirstPayloadRanking;
+
+        impl PayloadRanking for FirstPayloadRanking {
+            fn is_better(&self, current: Option<&dyn BuiltPayload>, _: U256) -> bool {
+                current.is_none()
+            }
+        }
+
+        let best = mock_payload(&mock_config(), U256::from(2));
+        assert!(FeeRanking.is_better(None, U256::ZERO));
+        assert!(FeeRanking.is_better(Some(&best), U256::from(3)));
+        assert!(!FeeRanking.is_better(Some(&best), U256::from(2)));
+
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Better(2)]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default()
+                .interval(interval)
+                .ranking(FirstPayloadRanking),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+
+        assert_eq!(builder.builds(), 2);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn build_interval_actual_metric() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        // the job is polled late, so the builds are spawned after the interval
+        tokio::time::advance(Duration::from_millis(1_500)).await;
+        run_build(&mut job).await;
+        tokio::time::advance(Duration::from_millis(1_250)).await;
+        run_build(&mut job).await;
+
+        assert_eq!(histogram("payloads.build_interval_actual"), vec![1.5, 1.25]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unchanged_pool_fingerprint_skips_builds() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::default().with_fingerprint(7);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        for _ in 0..2 {
+            tokio::time::advance(interval).await;
+            assert!(poll_once(&mut job).is_pending());
+            assert!(job.pending_block.is_none());
+        }
+        assert_eq!(builder.builds(), 1);
+        assert_eq!(counter("payloads.builds_skipped_unchanged_total"), 2);
+
+        // forced transactions are built even if the pool didn't change
+        let forced = vec![TransactionSigned::default()];
+        assert!(generator.jobs_handle().force_top_of_block(job.config().payload_id(), forced));
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(builder.builds(), 2);
+        assert_eq!(counter("payloads.builds_skipped_unchanged_total"), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn oversized_cached_reads_are_dropped() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let mut two_accounts = CachedReads::default();
+        for byte in 0..2 {
+            two_accounts.insert_account(
+                Address::with_last_byte(byte),
+                AccountInfo::default(),
+                Default::default(),
+            );
+        }
+        let interval = Duration::from_secs(1);
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default()
+                .interval(interval)
+                .max_cached_reads_bytes(Some(two_accounts.approx_size_bytes())),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        // every build caches one more account
+        run_build(&mut job).await;
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert!(job.cached_reads.is_some());
+        assert_eq!(counter("payloads.cached_reads_evicted_total"), 0);
+
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert!(job.cached_reads.is_none());
+        assert_eq!(counter("payloads.cached_reads_evicted_total"), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn value_over_empty_payload() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Better(3)]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default());
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.value_over_empty(), U256::ZERO);
+
+        run_build(&mut job).await;
+        assert_eq!(job.value_over_empty(), U256::from(3));
+
+        let _ = job.resolve();
+        assert_eq!(histogram("payloads.value_over_empty"), vec![3.0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn payload_task_permit_metrics() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().max_payload_tasks(2),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert_eq!(histogram("payloads.payload_task_acquire_duration"), vec![0.0]);
+        assert_eq!(gauge("payloads.payload_task_permits_available"), Some(2.0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn time_to_first_block_metric() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Failed,
+            MockOutcome::Better(1),
+            MockOutcome::Better(2),
+        ]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        run_build(&mut job).await;
+        assert!(histogram("payloads.payload_time_to_first_block").is_empty());
+
+        // recorded once for the first better payload
+        for _ in 0..2 {
+            tokio::time::advance(interval).await;
+            run_build(&mut job).await;
+        }
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(2));
+        assert_eq!(histogram("payloads.payload_time_to_first_block"), vec![1.5]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resolve_by_id_through_generator() {
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Better(3)]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default());
+        let attributes = mock_attributes(parent.hash());
+        let id = attributes.payload_id();
+        assert!(generator.resolve(id).is_none());
+
+        let mut job = generator.new_payload_job(attributes).unwrap();
+        run_build(&mut job).await;
+
+        let (fut, keep_alive) = generator.resolve(id).unwrap();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::Yes);
+        assert_eq!(fut.await.unwrap().fees(), U256::from(3));
+        // the job keeps its best payload
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(3));
+
+        drop(job);
+        assert!(generator.resolve(id).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn withdrawals_applied_metric() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Aborted]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let mut attributes = mock_attributes(parent.hash());
+        attributes.withdrawals = Withdrawals::new(
+            (0..3)
+                .map(|index| Withdrawal {
+                    index,
+                    validator_index: index,
+                    address: Address::with_last_byte(index as u8),
+                    amount: 1,
+                })
+                .collect(),
+        );
+        let mut job = generator.new_payload_job(attributes).unwrap();
+
+        // only the payload that became the best payload is recorded
+        run_build(&mut job).await;
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(histogram("payloads.withdrawals_applied"), vec![3.0]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn oversized_payload_is_rejected() {
+        let _ = DebuggingRecorder::per_thread().install();
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Better(2)]);
+        let (generator, parent) = mock_generator(
+            builder,
+            BasicPayloadJobGeneratorConfig::default()
+                .interval(interval)
+                .max_encoded_block_size(Some(2_000)),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert!(job.best_payload().unwrap().block().length() <= 2_000);
+
+        job.config.forced_top_of_block = vec![TransactionSigned::default(); 100];
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+
+        assert_eq!(counter("payloads.builds_rejected_oversize_total"), 1);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn extradata_fn_is_evaluated_per_job() {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let extradata_fn = Arc::new(move || Bytes::from(format!("reth/{started}")));
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().extradata_fn(extradata_fn.clone()),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        let extra_data = job.best_payload().unwrap().block().extra_data.clone();
+        assert!(extra_data.starts_with(b"reth/"));
+        assert_eq!(extra_data, extradata_fn());
+    }
+
+    #[test]
+    fn oversized_extradata_fn_is_ignored() {
+        let config = BasicPayloadJobGeneratorConfig::default()
+            .extradata(Bytes::from_static(b"static"))
+            .extradata_fn(Arc::new(|| Bytes::from(vec![0; MAXIMUM_EXTRA_DATA_SIZE + 1])));
+        assert_eq!(config.job_extradata(), Bytes::from_static(b"static"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[cfg_attr(debug_assertions, should_panic(expected = "payload built on wrong parent"))]
+    async fn rejects_payload_built_on_wrong_parent() {
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::WrongParent]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default());
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert!(job.best_payload.is_none());
+        assert_eq!(job.metrics_snapshot().failed_builds, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn build_once_spawns_single_build() {
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Better(2)]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().build_once(true),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        assert_eq!(builder.builds(), 1);
+        assert_eq!(job.best_payload.as_ref().unwrap().fees(), U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn empty_pool_payload_is_not_rebuilt() {
+        EMPTY_BUILDS.with(|builds| builds.set(0));
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Empty]);
+        let (generator, parent) =
+            mock_generator(builder.clone(), BasicPayloadJobGeneratorConfig::default());
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        let best = job.best_payload().unwrap();
+        assert!(best.block().body.is_empty());
+
+        // a job for the same attributes serves the empty payload before its first build
+        let other = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(other.best_payload().unwrap().block().hash(), best.block().hash());
+
+        assert_eq!(builder.builds(), 1);
+        assert_eq!(EMPTY_BUILDS.with(|builds| builds.get()), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn build_timeout_cancels_build() {
+        let timeout = Duration::from_millis(100);
+        let builder = UntilCancelledBuilder::default();
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().build_timeout(Some(timeout)),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_some());
+
+        tokio::time::advance(timeout).await;
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        assert_eq!(job.metrics_snapshot().failed_builds, 1);
+
+        while !builder.cancelled.load(Ordering::Relaxed) {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[test]
+    fn withdrawals_outcome_states() {
+        let pre_shanghai = WithdrawalsOutcome::pre_shanghai();
+        assert!(pre_shanghai.is_pre_shanghai());
+        assert!(!pre_shanghai.is_empty());
+
+        let empty = WithdrawalsOutcome::empty();
+        assert!(!empty.is_pre_shanghai());
+        assert!(empty.is_empty());
+
+        let withdrawals = Withdrawals::new(vec![Withdrawal { amount: 1, ..Default::default() }]);
+        let applied = WithdrawalsOutcome {
+            withdrawals_root: Some(proofs::calculate_withdrawals_root(&withdrawals)),
+            withdrawals: Some(withdrawals),
+        };
+        assert!(!applied.is_pre_shanghai());
+        assert!(!applied.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn job_exposes_config() {
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().extradata(Bytes::from_static(b"reth")),
+        );
+        let attributes = mock_attributes(parent.hash());
+        let job = generator.new_payload_job(attributes.clone()).unwrap();
+
+        let config = job.config();
+        assert_eq!(config.parent_block.hash(), parent.hash());
+        assert_eq!(config.extra_data, Bytes::from_static(b"reth"));
+        assert_eq!(config.attributes, attributes);
+        assert_eq!(config.payload_id(), attributes.id);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn metrics_snapshot_reflects_builds() {
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Failed, MockOutcome::Better(7)]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.metrics_snapshot(), JobMetrics::default());
+
+        job.best_payload().unwrap();
+        run_build(&mut job).await;
+        let snapshot = job.metrics_snapshot();
+        assert_eq!(snapshot.initiated_builds, 1);
+        assert_eq!(snapshot.failed_builds, 1);
+        assert_eq!(snapshot.empty_requests, 1);
+        assert_eq!(snapshot.best_fees, None);
+        assert!(snapshot.last_build_duration.is_some());
+
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        let snapshot = job.metrics_snapshot();
+        assert_eq!(snapshot.initiated_builds, 2);
+        assert_eq!(snapshot.failed_builds, 1);
+        assert_eq!(snapshot.empty_requests, 1);
+        assert_eq!(snapshot.best_fees, Some(U256::from(7)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn job_gives_up_after_repeated_withdrawal_failures() {
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::WithdrawalsFailed]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        for _ in 1..MAX_FATAL_BUILD_FAILURES {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        assert_eq!(builder.builds() as u32, MAX_FATAL_BUILD_FAILURES - 1);
+
+        // the last identical failure terminates the job
+        assert!(poll_once(&mut job).is_pending());
+        let res = loop {
+            tokio::task::yield_now().await;
+            if let Poll::Ready(res) = poll_once(&mut job) {
+                break res
+            }
+        };
+        let error = res.unwrap_err();
+        assert!(error.is_potentially_fatal());
+        assert!(matches!(error, PayloadBuilderError::WithdrawalBalanceIncrement(_)));
+        assert_eq!(builder.builds() as u32, MAX_FATAL_BUILD_FAILURES);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn repeated_job_failures_suspend_building() {
+        let interval = Duration::from_secs(1);
+        let cooldown = Duration::from_secs(12);
+        let failed_builds = 2 * MAX_FATAL_BUILD_FAILURES as usize;
+        let builder = MockPayloadBuilder::with_outcomes(
+            std::iter::repeat(MockOutcome::WithdrawalsFailed)
+                .take(failed_builds)
+                .chain([MockOutcome::Better(1)]),
+        );
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default()
+                .interval(interval)
+                .max_failed_jobs(Some(2))
+                .failed_jobs_cooldown(cooldown),
+        );
+
+        for _ in 0..2 {
+            let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+            let error = run_until_given_up(&mut job, interval).await;
+            assert!(error.is_potentially_fatal());
+        }
+        assert_eq!(builder.builds(), failed_builds);
+
+        // new jobs are rejected until the cooldown elapsed
+        assert!(matches!(
+            generator.new_payload_job(mock_attributes(parent.hash())),
+            Err(PayloadBuilderError::BuildingSuspended { failed_jobs: 2 })
+        ));
+        tokio::time::advance(cooldown / 2).await;
+        assert!(generator.new_payload_job(mock_attributes(parent.hash())).is_err());
+
+        // the first job after the cooldown probes whether building recovered
+        tokio::time::advance(cooldown).await;
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        run_build(&mut job).await;
+        assert!(job.best_payload.is_some());
+        assert_eq!(generator.circuit_breaker.state().failed_jobs, 0);
+        assert_eq!(generator.circuit_breaker.open_failed_jobs(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timed_out_build_is_retried() {
+        let error = PayloadBuilderError::BuildTimedOut { elapsed: Duration::from_secs(1) };
+        assert!(error.is_retryable());
+        assert!(!PayloadBuilderError::MissingParentBlock(B256::ZERO).is_retryable());
+
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::TimedOut, MockOutcome::Better(3)]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert_eq!(builder.builds(), 1);
+        assert_eq!(job.metrics_snapshot().failed_builds, 1);
+        assert!(job.best_payload.is_none());
+
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(builder.builds(), 2);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn builder_is_notified_of_better_payloads() {
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Better(1),
+            MockOutcome::Aborted,
+            MockOutcome::Better(2),
+            MockOutcome::Better(1),
+        ]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        for _ in 0..4 {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        assert_eq!(*builder.better.lock().unwrap(), vec![U256::from(1), U256::from(2)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn subscribers_receive_better_payloads() {
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Better(1),
+            MockOutcome::Aborted,
+            MockOutcome::Better(2),
+        ]);
+        let (generator, parent) =
+            mock_generator(builder, BasicPayloadJobGeneratorConfig::default().interval(interval));
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        let mut better_payloads = job.subscribe();
+
+        for _ in 0..3 {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        assert_eq!(better_payloads.try_recv().unwrap().fees(), U256::from(1));
+        assert_eq!(better_payloads.try_recv().unwrap().fees(), U256::from(2));
+        assert!(better_payloads.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_stops_building() {
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Better(1)]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval),
+        );
+        let shutdown = generator.shutdown_handle();
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        run_build(&mut job).await;
+        tokio::time::advance(interval).await;
+
+        // the generator is owned by the payload builder service once it runs
+        let new_payload_job = move |attributes| generator.new_payload_job(attributes);
+        let woken = Arc::new(WakeFlag::default());
+        let waker = futures_util::task::waker(Arc::clone(&woken));
+        assert!(Pin::new(&mut job).poll(&mut Context::from_waker(&waker)).is_pending());
+        assert!(job.pending_block.is_some());
+
+        shutdown.shutdown();
+        assert!(shutdown.is_shutdown());
+        assert!(woken.0.load(Ordering::Relaxed));
+        assert!(matches!(
+            new_payload_job(mock_attributes(parent.hash())),
+            Err(PayloadBuilderError::Shutdown)
+        ));
+
+        // the pending build is cancelled and the job stays alive without building
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        let builds = builder.builds();
+        tokio::time::advance(interval).await;
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        assert_eq!(builder.builds(), builds);
+
+        // the best payload can still be resolved
+        let (fut, _) = job.resolve();
+        assert_eq!(fut.await.unwrap().fees(), U256::from(1));
+    }
+
+    #[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+    struct TestEngineTypes;
+
+    impl EngineTypes for TestEngineTypes {
+        type PayloadAttributes = PayloadAttributes;
+        type PayloadBuilderAttributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+        type ExecutionPayloadV1 = ExecutionPayloadV1;
+        type ExecutionPayloadV2 = ExecutionPayloadEnvelopeV2;
+        type ExecutionPayloadV3 = ExecutionPayloadEnvelopeV3;
+
+        fn validate_version_specific_fields(
+            chain_spec: &ChainSpec,
+            version: EngineApiMessageVersion,
+            payload_or_attrs: PayloadOrAttributes<'_, PayloadAttributes>,
+        ) -> Result<(), EngineObjectValidationError> {
+            validate_version_specific_fields(chain_spec, version, payload_or_attrs)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_jobs_resolve_through_service() {
+        let builder = MockPayloadBuilder::with_outcomes([MockOutcome::Better(1)]);
+        let (generator, parent) =
+            mock_generator(builder.clone(), BasicPayloadJobGeneratorConfig::default());
+        let shutdown = generator.shutdown_handle();
+        let (service, handle) = PayloadBuilderService::<_, _, TestEngineTypes>::new(
+            generator,
+            futures_util::stream::empty(),
+        );
+        tokio::spawn(service);
+        let store = PayloadStore::from(handle.clone());
+
+        let id = handle.send_new_payload(mock_attributes(parent.hash())).await.unwrap().unwrap();
+        wait_for_builds(&builder, 1).await;
+        while store.best_payload(id).await.unwrap().unwrap().fees() != U256::from(1) {
+            tokio::task::yield_now().await;
+        }
+
+        shutdown.shutdown();
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+        }
+
+        // the service didn't drop the job
+        assert_eq!(store.resolve(id).await.unwrap().unwrap().fees(), U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_building_after_max_build_attempts() {
+        let interval = Duration::from_secs(1);
+        let builder = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Better(1),
+            MockOutcome::Better(2),
+            MockOutcome::Better(3),
+        ]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().interval(interval).max_build_attempts(2),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        for _ in 0..2 {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        tokio::time::advance(interval).await;
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        assert_eq!(builder.builds(), 2);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_building_after_max_stale_intervals() {
+        let interval = Duration::from_secs(1);
+        let builder =
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1), MockOutcome::Aborted]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default()
+                .interval(interval)
+                .max_stale_intervals(Some(2)),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        // one better build followed by two aborted builds
+        for _ in 0..3 {
+            run_build(&mut job).await;
+            tokio::time::advance(interval).await;
+        }
+        assert_eq!(builder.builds(), 3);
+
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        tokio::time::advance(interval).await;
+        assert!(poll_once(&mut job).is_pending());
+        assert!(job.pending_block.is_none());
+        assert_eq!(builder.builds(), 3);
+        assert_eq!(job.best_payload().unwrap().fees(), U256::from(1));
+
+        job.reset_stale_intervals();
+        tokio::time::advance(interval).await;
+        run_build(&mut job).await;
+        assert_eq!(builder.builds(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn multi_builder_serves_most_valuable_payload() {
+        let client = MockEthProvider::default();
+        let parent =
+            Block { header: Header { number: 1, ..Default::default() }, ..Default::default() }
+                .seal_slow();
+        client.add_block(parent.hash(), parent.clone().unseal());
+        let low = MockPayloadBuilder::with_outcomes([MockOutcome::Better(3)]);
+        let high = MockPayloadBuilder::with_outcomes([MockOutcome::Better(8)]);
+        let generators = [low.clone(), high.clone()]
+            .into_iter()
+            .map(|builder| {
+                mock_generator_with_client(
+                    client.clone(),
+                    builder,
+                    BasicPayloadJobGeneratorConfig::default(),
+                )
+            })
+            .collect();
+        let generator = MultiBuilderGenerator::new(generators);
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        while low.builds() == 0 || job.best_payload().unwrap().fees() != U256::from(8) {
+            assert!(poll_once(&mut job).is_pending());
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(high.builds(), 1);
+
+        let (fut, keep_alive) = job.resolve();
+        assert_eq!(keep_alive, KeepPayloadJobAlive::No);
+        assert_eq!(fut.await.unwrap().fees(), U256::from(8));
+    }
+
+    #[tokio::test]
+    async fn payload_task_guard_resize() {
+        let guard = PayloadTaskGuard::new(3);
+        let first = guard.acquire().await.unwrap();
+        let second = guard.acquire().await.unwrap();
+
+        // the available permit is forgotten right away, the held ones once released
+        guard.resize(1);
+        assert_eq!(guard.semaphore.available_permits(), 0);
+        drop(first);
+        assert_eq!(guard.semaphore.available_permits(), 0);
+        drop(second);
+        assert_eq!(guard.semaphore.available_permits(), 1);
+
+        let held = guard.acquire().await.unwrap();
+        guard.resize(0);
+        // growing again keeps the permit that wasn't forgotten yet
+        guard.resize(2);
+        assert_eq!(guard.semaphore.available_permits(), 1);
+        drop(held);
+        assert_eq!(guard.semaphore.available_permits(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resize_payload_tasks_through_handle() {
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().max_payload_tasks(2),
+        );
+        let tasks = generator.payload_tasks_handle();
+        // the generator is owned by the payload builder service once it runs
+        let new_payload_job = move |attributes| generator.new_payload_job(attributes);
+
+        tasks.set_max_payload_tasks(1);
+        assert_eq!(tasks.max_payload_tasks(), 1);
+        assert_eq!(tasks.0.available_permits(), 1);
+
+        // a build holds the only permit
+        let _permit = tasks.0.acquire().await.unwrap();
+        let mut job = new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert!(poll_once(&mut job).is_pending());
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+            assert!(poll_once(&mut job).is_pending());
+        }
+        assert!(job.best_payload.is_none());
+
+        tasks.set_max_payload_tasks(2);
+        assert_eq!(tasks.max_payload_tasks(), 2);
+        while job.pending_block.is_some() {
+            tokio::task::yield_now().await;
+            assert!(poll_once(&mut job).is_pending());
+        }
+        assert!(job.best_payload.is_some());
+    }
+
+    #[test]
+    fn build_arguments_builder() {
+        let args = BuildArguments::<_, _, _, EthBuiltPayload>::builder(
+            MockEthProvider::default(),
+            NoopTransactionPool::default(),
+            mock_config(),
+        )
+        .build();
+        assert!(args.best_payload.is_none());
+        assert!(!args.cancel.is_cancelled());
+        assert_eq!(args.cached_reads.approx_size_bytes(), 0);
+
+        let config = mock_config();
+        let best_payload = mock_payload(&config, U256::from(7));
+        let cancel = Cancelled::default();
+        let args = BuildArguments::builder(
+            MockEthProvider::default(),
+            NoopTransactionPool::default(),
+            config,
+        )
+        .with_cancel(cancel.clone())
+        .with_best_payload(Some(best_payload))
+        .build();
+        assert_eq!(args.best_payload.as_ref().map(|payload| payload.fees()), Some(U256::from(7)));
+
+        drop(cancel);
+        assert!(args.cancel.is_cancelled());
+    }
+
+    #[test]
+    fn remaining_gas_of_config_gas_limit() {
+        let mut config = mock_config();
+        config.initialized_block_env.gas_limit = U256::from(30_000_000);
+        let args = BuildArguments::<_, _, _, EthBuiltPayload>::new(
+            MockEthProvider::default(),
+            NoopTransactionPool::default(),
+            CachedReads::default(),
+            config,
+            Cancelled::default(),
+            None,
+        );
+
+        assert_eq!(args.remaining_gas(0), 30_000_000);
+        assert_eq!(args.remaining_gas(21_000), 29_979_000);
+        assert_eq!(args.remaining_gas(30_000_000), 0);
+        assert_eq!(args.remaining_gas(u64::MAX), 0);
+    }
+
+    #[tokio::test]
+    async fn min_gas_limit_is_applied() {
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().min_gas_limit(10_000),
+        );
+        assert!(parent.gas_limit < 10_000);
+        let job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.config().initialized_block_env.gas_limit, U256::from(10_000));
+
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        let job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.config().initialized_block_env.gas_limit, U256::from(MINIMUM_GAS_LIMIT));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gas_limit_override_is_applied_to_jobs() {
+        let gas_limit = 40_000;
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default().with_gas_limit(gas_limit),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        assert_ne!(parent.gas_limit, gas_limit);
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.config().initialized_block_env.gas_limit, U256::from(gas_limit));
+
+        // the builds of the job use the overridden gas limit
+        run_build(&mut job).await;
+        assert_eq!(job.best_payload().unwrap().block().gas_limit, gas_limit);
+
+        // the minimum gas limit still applies
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default().with_gas_limit(gas_limit),
+            BasicPayloadJobGeneratorConfig::default().min_gas_limit(50_000),
+        );
+        let job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert_eq!(job.config().initialized_block_env.gas_limit, U256::from(50_000));
+    }
+
+    #[test]
+    fn detects_unsupported_upcoming_forks() {
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(100))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(200))
+            .build();
+        let supported = [Hardfork::Paris, Hardfork::Shanghai];
+
+        assert_eq!(unsupported_upcoming_forks(&chain_spec, &supported, 50), vec![Hardfork::Cancun]);
+        assert!(unsupported_upcoming_forks(&chain_spec, &supported, 200).is_empty());
+        assert!(unsupported_upcoming_forks(
+            &chain_spec,
+            &[Hardfork::Shanghai, Hardfork::Cancun],
+            50
+        )
+        .is_empty());
+        assert_eq!(
+            <MockPayloadBuilder as PayloadBuilder<(), ()>>::supported_forks(
+                &MockPayloadBuilder::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn elapsed_in_slot() {
+        let config = mock_config();
+        let timestamp = config.timestamp();
+        assert_eq!(timestamp, config.attributes.timestamp);
+
+        assert_eq!(config.elapsed_in_slot_with(&FixedClock(timestamp - 1)), Duration::ZERO);
+        assert_eq!(config.elapsed_in_slot_with(&FixedClock(timestamp)), Duration::ZERO);
+        assert_eq!(config.elapsed_in_slot_with(&FixedClock(timestamp + 4)), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn best_execution_payload_of_cancun_config() {
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::with_outcomes([MockOutcome::Better(1)]),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+        assert!(MAINNET.is_cancun_active_at_timestamp(job.config().attributes.timestamp));
+        run_build(&mut job).await;
+
+        let best = job.best_payload().unwrap();
+        let ExecutionPayload::V3(payload) = job.best_execution_payload().unwrap() else {
+            panic!("expected a V3 payload")
+        };
+        assert_eq!(payload.payload_inner.payload_inner.block_hash, best.block().hash());
+    }
+
+    #[tokio::test]
+    async fn identical_empty_payload_is_cached() {
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        let empty_builds = || EMPTY_BUILDS.with(|builds| builds.get());
+        let attributes = mock_attributes(parent.hash());
+
+        let first = generator.new_payload_job(attributes.clone()).unwrap();
+        let payload = first.best_payload().unwrap();
+        assert_eq!(empty_builds(), 1);
+
+        let second = generator.new_payload_job(attributes.clone()).unwrap();
+        assert_eq!(second.best_payload().unwrap().block().hash(), payload.block().hash());
+        assert_eq!(empty_builds(), 1);
+
+        // different attributes build a new empty payload
+        let other =
+            EthPayloadBuilderAttributes { timestamp: attributes.timestamp + 1, ..attributes };
+        let third = generator.new_payload_job(other).unwrap();
+        assert_ne!(third.best_payload().unwrap().block().hash(), payload.block().hash());
+        assert_eq!(empty_builds(), 2);
+    }
+
+    #[tokio::test]
+    async fn empty_payload_cache_is_keyed_on_config() {
+        let empty_builds = || EMPTY_BUILDS.with(|builds| builds.get());
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default(),
+        );
+        let attributes = mock_attributes(parent.hash());
+        let first = generator.new_payload_job(attributes.clone()).unwrap();
+        let payload = first.best_payload().unwrap();
+        let builds = empty_builds();
+
+        // attributes with another id but the same payload fields share the empty payload
+        let other =
+            EthPayloadBuilderAttributes { id: PayloadId::new([2; 8]), ..attributes.clone() };
+        let second = generator.new_payload_job(other).unwrap();
+        assert_eq!(second.best_payload().unwrap().block().hash(), payload.block().hash());
+        assert_eq!(empty_builds(), builds);
+
+        // empty payloads aren't cached if the bundle state hook must observe them
+        let (generator, parent) = mock_generator(
+            MockPayloadBuilder::default(),
+            BasicPayloadJobGeneratorConfig::default().debug_bundle_state(Arc::new(|_| {})),
+        );
+        let attributes = mock_attributes(parent.hash());
+        let first = generator.new_payload_job(attributes.clone()).unwrap();
+        first.best_payload().unwrap();
+        let second = generator.new_payload_job(attributes).unwrap();
+        second.best_payload().unwrap();
+        assert_eq!(empty_builds(), builds + 2);
+    }
+
+    #[test]
+    fn config_with_extra_data() {
+        let config = mock_config();
+        let extra_data = Bytes::from_static(b"builder");
+        let updated = config.clone().with_extra_data(extra_data.clone());
+
+        assert_eq!(updated.extra_data(), extra_data);
+        assert_ne!(config.extra_data(), extra_data);
+        assert_eq!(updated.initialized_block_env, config.initialized_block_env);
+        assert_eq!(updated.attributes, config.attributes);
+        assert!(Arc::ptr_eq(&updated.parent_block, &config.parent_block));
+        assert!(Arc::ptr_eq(&updated.chain_spec, &config.chain_spec));
+    }
+
+    #[test]
+    #[should_panic]
+    fn config_with_oversized_extra_data() {
+        mock_config().with_extra_data(vec![0; MAXIMUM_EXTRA_DATA_SIZE + 1].into());
+    }
+
+    #[test]
+    fn latest_state_of_canonical_ancestor() {
+        let client = MockEthProvider::default();
+        let ancestor =
+            Block { header: Header { number: 1, ..Default::default() }, ..Default::default() }
+                .seal_slow();
+        client.add_block(ancestor.hash(), ancestor.clone().unseal());
+        let latest = Block {
+            header: Header { number: 2, parent_hash: ancestor.hash(), ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+        client.add_block(latest.hash(), latest.clone().unseal());
+        let sidechain = Block {
+            header: Header { number: 1, gas_limit: 1, ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+
+        assert!(latest_state_of_ancestor(&client, &ancestor).unwrap().is_some());
+        assert!(latest_state_of_ancestor(&client, &latest).unwrap().is_some());
+        assert!(latest_state_of_ancestor(&client, &sidechain).unwrap().is_none());
+    }
+
+    #[test]
+    fn cached_payload_state_reuses_cached_reads() {
+        let address = Address::random();
+        let mut db = revm::db::InMemoryDB::default();
+        db.insert_account_info(address, AccountInfo { nonce: 1, ..Default::default() });
+
+        let mut cached_reads = CachedReads::default();
+        let mut state = cached_payload_state(&mut cached_reads, &db);
+        state.increment_balances([(address, 1)]).unwrap();
+        drop(state);
+
+        // a later build on the same parent reads the account from the cache
+        let empty = revm::db::EmptyDB::default();
+        let mut state = cached_payload_state(&mut cached_reads, &empty);
+        state.increment_balances([(address, 1)]).unwrap();
+        state.merge_transitions(BundleRetention::PlainState);
+        let info = state.take_bundle().account(&address).unwrap().info.clone().unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.balance, U256::from(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keeps_best_candidate() {
+        let builder = MockPayloadBuilder::with_outcomes([
+            MockOutcome::Better(1),
+            MockOutcome::Better(5),
+            MockOutcome::Better(3),
+        ]);
+        let (generator, parent) = mock_generator(
+            builder.clone(),
+            BasicPayloadJobGeneratorConfig::default().candidates_per_build(3),
+        );
+        let mut job = generator.new_payload_job(mock_attributes(parent.hash())).unwrap();
+
+        run_build(&mut job).await;
+        assert_eq!(builder.builds(), 3);
+        assert_eq!(job.best_payload.unwrap().fees(), U256::from(5));
+    }
+
+    /// Returns the excess blob gas of a child of a post-Cancun parent with the given blob fields.
+    fn child_excess_blob_gas(excess_blob_gas: u64, blob_gas_used: u64) -> u64 {
+        let chain_spec = ChainSpecBuilder::mainnet().cancun_activated().build();
+        let parent = Header {
+            excess_blob_gas: Some(excess_blob_gas),
+            blob_gas_used: Some(blob_gas_used),
+            ..Default::default()
+        };
+        compute_excess_blob_gas(&chain_spec, &parent)
+    }
+
+    #[test]
+    fn excess_blob_gas_update_rule() {
+        // zero
+        assert_eq!(child_excess_blob_gas(0, 0), 0);
+        // below and at target, the excess is drained
+        assert_eq!(child_excess_blob_gas(0, TARGET_DATA_GAS_PER_BLOCK - 1), 0);
+        assert_eq!(child_excess_blob_gas(0, TARGET_DATA_GAS_PER_BLOCK), 0);
+        assert_eq!(child_excess_blob_gas(TARGET_DATA_GAS_PER_BLOCK, 0), 0);
+        assert_eq!(child_excess_blob_gas(TARGET_DATA_GAS_PER_BLOCK + 1, 0), 1);
+        // above target, the excess grows by the surplus
+        assert_eq!(child_excess_blob_gas(0, TARGET_DATA_GAS_PER_BLOCK + 1), 1);
+        assert_eq!(
+            child_excess_blob_gas(0, MAX_DATA_GAS_PER_BLOCK),
+            MAX_DATA_GAS_PER_BLOCK - TARGET_DATA_GAS_PER_BLOCK
+        );
+        assert_eq!(child_excess_blob_gas(42, TARGET_DATA_GAS_PER_BLOCK), 42);
+    }
+
+    #[test]
+    fn excess_blob_gas_of_first_cancun_block() {
+        let chain_spec = ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build();
+        // the blob fields of a pre-Cancun parent are evaluated as 0
+        let parent = Header {
+            excess_blob_gas: Some(MAX_DATA_GAS_PER_BLOCK),
+            blob_gas_used: Some(MAX_DATA_GAS_PER_BLOCK),
+            ..Default::default()
+        };
+        assert_eq!(compute_excess_blob_gas(&chain_spec, &parent), 0);
+    }
+
+    /// A [PayloadClock] at a fixed unix timestamp.
+    #[derive(Debug)]
+    struct FixedClock(u64);
+
+    impl PayloadClock for FixedClock {
+        fn now(&self) -> SystemTime {
+            UNIX_EPOCH + Duration::from_secs(self.0)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn job_deadline_is_bounded() {
+        let deadline = Duration::from_secs(12);
+        let generator = |max_deadline| {
+            mock_generator(
+                MockPayloadBuilder::default(),
+                BasicPayloadJobGeneratorConfig::default()
+                    .deadline(deadline)
+                    .max_deadline(max_deadline),
+            )
+            .0
+            .with_clock(FixedClock(1_000))
+        };
+        // an hour in the future
+        let timestamp = 1_000 + 3_600;
+        let now = tokio::time::Instant::now();
+
+        assert_eq!(generator(None).job_deadline(timestamp), now + deadline * 4);
+        assert_eq!(
+            generator(Some(Duration::from_secs(20))).job_deadline(timestamp),
+            now + Duration::from_secs(20)
+        );
+        // the bound doesn't extend the deadline
+        assert_eq!(generator(Some(Duration::from_secs(20))).job_deadline(1_000), now + deadline);
+    }
+
+    #[test]
+    fn max_job_duration_with_skewed_clock() {
+        let deadline = Duration::from_secs(12);
+        let generator = |now| {
+            mock_generator(
+                MockPayloadBuilder::default(),
+                BasicPayloadJobGeneratorConfig::default().deadline(deadline),
+            )
+            .0
+            .with_clock(FixedClock(now))
+        };
+        let timestamp = 1_000;
+
+        // clock behind the payload timestamp
+        assert_eq!(generator(990).max_job_duration(timestamp), Duration::from_secs(22));
+        // clock far behind is clamped
+        assert_eq!(generator(0).max_job_duration(timestamp), deadline * 4);
+        // clock past the payload timestamp
+        assert_eq!(generator(1_010).max_job_duration(timestamp), deadline);
+    }
 }
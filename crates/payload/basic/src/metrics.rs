@@ -1,9 +1,18 @@
 //! Metrics for the payload builder impl
 
-use reth_metrics::{metrics::Counter, Metrics};
+use reth_metrics::{
+    metrics::{Counter, Gauge, Histogram},
+    Metrics,
+};
+use reth_primitives::U256;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::time::Instant;
 
 /// Transaction pool metrics
-#[derive(Metrics)]
+#[derive(Clone, Metrics)]
 #[metrics(scope = "payloads")]
 pub(crate) struct PayloadBuilderMetrics {
     /// Number of active jobs
@@ -12,6 +21,32 @@ pub(crate) struct PayloadBuilderMetrics {
     pub(crate) initiated_payload_builds: Counter,
     /// Total number of failed payload build attempts
     pub(crate) failed_payload_builds: Counter,
+    /// Total number of payload builds aborted because the payload was worse than the best
+    pub(crate) builds_aborted_worse_total: Counter,
+    /// Total number of cancelled payload builds
+    pub(crate) builds_cancelled_total: Counter,
+    /// Total number of better payloads that were identical to the best payload
+    pub(crate) redundant_better_total: Counter,
+    /// Total number of better payloads rejected because their encoded block exceeds the limit
+    pub(crate) builds_rejected_oversize_total: Counter,
+    /// Total number of intervals skipped because the best transactions of the pool didn't change
+    pub(crate) builds_skipped_unchanged_total: Counter,
+    /// Total number of times the cached reads of a job were dropped for exceeding the size limit
+    pub(crate) cached_reads_evicted_total: Counter,
+    /// Number of transactions in better payloads
+    pub(crate) payload_tx_count: Histogram,
+    /// Number of withdrawals in better payloads after Shanghai
+    pub(crate) withdrawals_applied: Histogram,
+    /// Fees in wei of the best payload over an empty payload when a job is resolved
+    pub(crate) value_over_empty: Histogram,
+    /// Elapsed time in seconds between consecutive payload build attempts of a job
+    pub(crate) build_interval_actual: Histogram,
+    /// Elapsed time in seconds from the creation of a job until its first better payload
+    pub(crate) payload_time_to_first_block: Histogram,
+    /// Time in seconds a build task waited for a permit to execute
+    pub(crate) payload_task_acquire_duration: Histogram,
+    /// Number of permits currently available to build tasks
+    pub(crate) payload_task_permits_available: Gauge,
 }
 
 impl PayloadBuilderMetrics {
@@ -26,4 +61,117 @@ impl PayloadBuilderMetrics {
     pub(crate) fn inc_failed_payload_builds(&self) {
         self.failed_payload_builds.increment(1);
     }
+
+    pub(crate) fn inc_builds_aborted_worse(&self) {
+        self.builds_aborted_worse_total.increment(1);
+    }
+
+    pub(crate) fn inc_builds_cancelled(&self) {
+        self.builds_cancelled_total.increment(1);
+    }
+
+    pub(crate) fn inc_redundant_better(&self) {
+        self.redundant_better_total.increment(1);
+    }
+
+    pub(crate) fn inc_builds_rejected_oversize(&self) {
+        self.builds_rejected_oversize_total.increment(1);
+    }
+
+    pub(crate) fn inc_builds_skipped_unchanged(&self) {
+        self.builds_skipped_unchanged_total.increment(1);
+    }
+
+    pub(crate) fn inc_cached_reads_evicted(&self) {
+        self.cached_reads_evicted_total.increment(1);
+    }
+
+    pub(crate) fn record_payload_tx_count(&self, tx_count: usize) {
+        self.payload_tx_count.record(tx_count as f64);
+    }
+
+    pub(crate) fn record_withdrawals_applied(&self, withdrawal_count: usize) {
+        self.withdrawals_applied.record(withdrawal_count as f64);
+    }
+
+    pub(crate) fn record_build_interval_actual(&self, elapsed: Duration) {
+        self.build_interval_actual.record(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn record_time_to_first_block(&self, elapsed: Duration) {
+        self.payload_time_to_first_block.record(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn record_task_acquire_duration(&self, elapsed: Duration) {
+        self.payload_task_acquire_duration.record(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn set_task_permits_available(&self, permits: usize) {
+        self.payload_task_permits_available.set(permits as f64);
+    }
+
+    pub(crate) fn record_value_over_empty(&self, value: U256) {
+        self.value_over_empty.record(value.saturating_to::<u128>() as f64);
+    }
+}
+
+/// A snapshot of the build statistics of a single payload job.
+///
+/// See [BasicPayloadJob::metrics_snapshot](crate::BasicPayloadJob::metrics_snapshot).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JobMetrics {
+    /// Number of initiated payload build attempts
+    pub initiated_builds: u64,
+    /// Number of failed payload build attempts
+    pub failed_builds: u64,
+    /// Number of times an empty payload was requested because no payload was built yet
+    pub empty_requests: u64,
+    /// Fees of the best payload built so far
+    pub best_fees: Option<U256>,
+    /// Duration of the last finished payload build attempt
+    pub last_build_duration: Option<Duration>,
+}
+
+/// Build statistics tracked by a single payload job.
+#[derive(Debug, Default)]
+pub(crate) struct JobStats {
+    initiated_builds: u64,
+    failed_builds: u64,
+    /// Incremented when resolving the best payload, which only has shared access to the job
+    empty_requests: AtomicU64,
+    /// When the in progress build attempt was started
+    build_started: Option<Instant>,
+    last_build_duration: Option<Duration>,
+}
+
+impl JobStats {
+    pub(crate) fn on_build_started(&mut self) {
+        self.initiated_builds += 1;
+        self.build_started = Some(Instant::now());
+    }
+
+    pub(crate) fn on_build_finished(&mut self) {
+        if let Some(started) = self.build_started.take() {
+            self.last_build_duration = Some(started.elapsed());
+        }
+    }
+
+    pub(crate) fn inc_failed_builds(&mut self) {
+        self.failed_builds += 1;
+    }
+
+    pub(crate) fn inc_empty_requests(&self) {
+        self.empty_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, best_fees: Option<U256>) -> JobMetrics {
+        JobMetrics {
+            initiated_builds: self.initiated_builds,
+            failed_builds: self.failed_builds,
+            empty_requests: self.empty_requests.load(Ordering::Relaxed),
+            best_fees,
+            last_build_duration: self.last_build_duration,
+        }
+    }
 }
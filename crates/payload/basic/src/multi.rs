@@ -0,0 +1,221 @@
+//! A [PayloadJobGenerator] that runs the jobs of multiple generators side by side.
+
+use futures_util::FutureExt;
+use reth_node_api::BuiltPayload;
+use reth_payload_builder::{
+    error::PayloadBuilderError, KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator,
+};
+use reth_provider::CanonStateNotification;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tracing::{debug, trace, warn};
+
+/// A [PayloadJobGenerator] that creates a job for every configured generator and serves the most
+/// valuable payload of all of them.
+///
+/// This allows running multiple builder strategies for the same payload, for example a
+/// [BasicPayloadJobGenerator](crate::BasicPayloadJobGenerator) per
+/// [PayloadBuilder](crate::PayloadBuilder).
+#[derive(Debug)]
+pub struct MultiBuilderGenerator<Generator> {
+    /// The generators that create the jobs of every payload.
+    generators: Vec<Generator>,
+}
+
+// === impl MultiBuilderGenerator ===
+
+impl<Generator> MultiBuilderGenerator<Generator> {
+    /// Creates a new generator that runs the jobs of all given generators.
+    ///
+    /// # Panics
+    ///
+    /// If `generators` is empty.
+    pub fn new(generators: Vec<Generator>) -> Self {
+        assert!(!generators.is_empty(), "at least one generator is required");
+        Self { generators }
+    }
+
+    /// Returns the configured generators.
+    pub fn generators(&self) -> &[Generator] {
+        &self.generators
+    }
+}
+
+impl<Generator> PayloadJobGenerator for MultiBuilderGenerator<Generator>
+where
+    Generator: PayloadJobGenerator,
+    Generator::Job: Unpin,
+    <Generator::Job as PayloadJob>::BuiltPayload: Unpin + 'static,
+    <Generator::Job as PayloadJob>::PayloadAttributes: Clone,
+{
+    type Job = MultiBuilderJob<Generator::Job>;
+
+    fn new_payload_job(
+        &self,
+        attributes: <Self::Job as PayloadJob>::PayloadAttributes,
+    ) -> Result<Self::Job, PayloadBuilderError> {
+        let jobs = self
+            .generators
+            .iter()
+            .map(|generator| generator.new_payload_job(attributes.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MultiBuilderJob { jobs: jobs.into_iter().map(Some).collect() })
+    }
+
+    fn on_new_state(&mut self, new_state: CanonStateNotification) {
+        for generator in &mut self.generators {
+            generator.on_new_state(new_state.clone());
+        }
+    }
+}
+
+/// A [PayloadJob] that drives the jobs of multiple builders and serves the most valuable payload.
+///
+/// The job resolves once all of its jobs resolved.
+#[derive(Debug)]
+pub struct MultiBuilderJob<Job> {
+    /// The jobs of all builders, `None` once a job finished.
+    jobs: Vec<Option<Job>>,
+}
+
+impl<Job> MultiBuilderJob<Job>
+where
+    Job: PayloadJob,
+{
+    /// Returns the jobs that are still running.
+    fn running(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().flatten()
+    }
+}
+
+impl<Job> Future for MultiBuilderJob<Job>
+where
+    Job: PayloadJob + Unpin,
+{
+    type Output = Result<(), PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        for slot in &mut this.jobs {
+            let Some(job) = slot else { continue };
+            if let Poll::Ready(res) = job.poll_unpin(cx) {
+                if let Err(error) = res {
+                    warn!(target: "payload_builder", %error, "builder job failed");
+                }
+                *slot = None;
+            }
+        }
+
+        if this.jobs.iter().all(Option::is_none) {
+            trace!(target: "payload_builder", "all builder jobs finished");
+            return Poll::Ready(Ok(()))
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Job> PayloadJob for MultiBuilderJob<Job>
+where
+    Job: PayloadJob + Unpin,
+    Job::BuiltPayload: Unpin + 'static,
+{
+    type PayloadAttributes = Job::PayloadAttributes;
+    type ResolvePayloadFuture = ResolveBestOfPayloads<Job::ResolvePayloadFuture, Job::BuiltPayload>;
+    type BuiltPayload = Job::BuiltPayload;
+
+    fn best_payload(&self) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        let mut best: Option<Self::BuiltPayload> = None;
+        let mut last_error = None;
+        for res in self.running().map(PayloadJob::best_payload) {
+            match res {
+                Ok(payload) => {
+                    if best.as_ref().map_or(true, |best| payload.fees() > best.fees()) {
+                        best = Some(payload);
+                    }
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        best.ok_or_else(|| last_error.unwrap_or(PayloadBuilderError::ChannelClosed))
+    }
+
+    fn payload_attributes(&self) -> Result<Self::PayloadAttributes, PayloadBuilderError> {
+        self.running().next().ok_or(PayloadBuilderError::ChannelClosed)?.payload_attributes()
+    }
+
+    fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
+        let mut keep_alive = KeepPayloadJobAlive::No;
+        let mut pending = Vec::with_capacity(self.jobs.len());
+        for slot in &mut self.jobs {
+            let Some(job) = slot else { continue };
+            let (fut, job_keep_alive) = job.resolve();
+            pending.push(Box::pin(fut));
+            if job_keep_alive == KeepPayloadJobAlive::Yes {
+                keep_alive = KeepPayloadJobAlive::Yes;
+            } else {
+                *slot = None;
+            }
+        }
+
+        (ResolveBestOfPayloads { pending, best: None, last_error: None }, keep_alive)
+    }
+}
+
+/// Resolves the most valuable payload of all given resolve futures.
+///
+/// This fails only if all futures failed.
+#[derive(Debug)]
+pub struct ResolveBestOfPayloads<Fut, Payload> {
+    /// Resolve futures that haven't finished yet.
+    pending: Vec<Pin<Box<Fut>>>,
+    /// The most valuable payload resolved so far.
+    best: Option<Payload>,
+    /// The last error of a failed future.
+    last_error: Option<PayloadBuilderError>,
+}
+
+impl<Fut, Payload> Future for ResolveBestOfPayloads<Fut, Payload>
+where
+    Fut: Future<Output = Result<Payload, PayloadBuilderError>>,
+    Payload: BuiltPayload + Unpin,
+{
+    type Output = Result<Payload, PayloadBuilderError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let mut idx = 0;
+        while idx < this.pending.len() {
+            let Poll::Ready(res) = this.pending[idx].as_mut().poll(cx) else {
+                idx += 1;
+                continue
+            };
+            drop(this.pending.swap_remove(idx));
+            match res {
+                Ok(payload) => {
+                    if this.best.as_ref().map_or(true, |best| payload.fees() > best.fees()) {
+                        this.best = Some(payload);
+                    }
+                }
+                Err(error) => {
+                    debug!(target: "payload_builder", %error, "failed to resolve builder payload");
+                    this.last_error = Some(error);
+                }
+            }
+        }
+
+        if !this.pending.is_empty() {
+            return Poll::Pending
+        }
+
+        Poll::Ready(match this.best.take() {
+            Some(best) => Ok(best),
+            None => Err(this.last_error.take().unwrap_or(PayloadBuilderError::ChannelClosed)),
+        })
+    }
+}
@@ -0,0 +1,224 @@
+//! A [PayloadBuilder] that orders the pool transactions with a custom comparator.
+
+use crate::{
+    simple::build_simple_payload, BuildArguments, BuildOutcome, PayloadBuilder, PayloadConfig,
+    SimplePayloadBuilder,
+};
+use reth_payload_builder::{
+    error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
+};
+use reth_primitives::Address;
+use reth_provider::StateProviderFactory;
+use reth_transaction_pool::{
+    BestTransactions, PoolTransaction, TransactionPool, ValidPoolTransaction,
+};
+use std::{cmp::Ordering, collections::HashSet, fmt, sync::Arc};
+
+/// A [PayloadBuilder] that sorts the best transactions of the pool with a comparator before
+/// greedily including them until the block is full.
+///
+/// This makes the ordering policy, for example FIFO or by gas price, a parameter of the builder.
+/// The sort is stable, so transactions the comparator considers equal keep the order of the pool.
+/// Transactions that are ordered before a transaction of the same sender with a lower nonce fail
+/// to execute and are skipped together with all later transactions of that sender.
+///
+/// Like the [SimplePayloadBuilder], blob transactions are skipped.
+#[derive(Clone)]
+pub struct OrderedPayloadBuilder<F> {
+    /// Compares two pool transactions, lesser transactions are included first.
+    compare: F,
+}
+
+impl<F> OrderedPayloadBuilder<F> {
+    /// Creates a new builder that orders the pool transactions with the given comparator.
+    pub fn new(compare: F) -> Self {
+        Self { compare }
+    }
+}
+
+impl<F> fmt::Debug for OrderedPayloadBuilder<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderedPayloadBuilder").finish_non_exhaustive()
+    }
+}
+
+impl<Pool, Client, F> PayloadBuilder<Pool, Client> for OrderedPayloadBuilder<F>
+where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+    F: Fn(
+            &ValidPoolTransaction<Pool::Transaction>,
+            &ValidPoolTransaction<Pool::Transaction>,
+        ) -> Ordering
+        + Send
+        + Sync
+        + Clone,
+{
+    type Attributes = EthPayloadBuilderAttributes;
+    type BuiltPayload = EthBuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        let BuildArguments { client, pool, cached_reads, config, cancel, best_payload } = args;
+
+        let mut best_txs = pool.best_transactions();
+        best_txs.no_updates();
+        let mut txs = best_txs.collect::<Vec<_>>();
+        txs.sort_by(|a, b| (self.compare)(a, b));
+
+        build_simple_payload(
+            &client,
+            Some(Box::new(SortedTransactions::new(txs))),
+            cached_reads,
+            config,
+            &cancel,
+            best_payload,
+        )
+    }
+
+    fn build_empty_payload(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        <SimplePayloadBuilder as PayloadBuilder<Pool, Client>>::build_empty_payload(client, config)
+    }
+}
+
+/// Yields already sorted pool transactions.
+///
+/// Invalid transactions are removed together with all later transactions of the same sender.
+struct SortedTransactions<T: PoolTransaction> {
+    /// The remaining transactions.
+    txs: std::vec::IntoIter<Arc<ValidPoolTransaction<T>>>,
+    /// Senders of invalid transactions.
+    invalid: HashSet<Address>,
+    /// Whether blob transactions are skipped.
+    skip_blobs: bool,
+}
+
+impl<T: PoolTransaction> SortedTransactions<T> {
+    fn new(txs: Vec<Arc<ValidPoolTransaction<T>>>) -> Self {
+        Self { txs: txs.into_iter(), invalid: HashSet::default(), skip_blobs: false }
+    }
+}
+
+impl<T: PoolTransaction> Iterator for SortedTransactions<T> {
+    type Item = Arc<ValidPoolTransaction<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let tx = self.txs.next()?;
+            if self.invalid.contains(&tx.sender()) {
+                continue
+            }
+            if self.skip_blobs && tx.is_eip4844() {
+                // descendants of a skipped blob transaction can't be included either
+                self.invalid.insert(tx.sender());
+                continue
+            }
+            return Some(tx)
+        }
+    }
+}
+
+impl<T: PoolTransaction> BestTransactions for SortedTransactions<T> {
+    fn mark_invalid(&mut self, transaction: &Self::Item) {
+        self.invalid.insert(transaction.sender());
+    }
+
+    fn no_updates(&mut self) {}
+
+    fn skip_blobs(&mut self) {
+        self.set_skip_blobs(true)
+    }
+
+    fn set_skip_blobs(&mut self, skip_blobs: bool) {
+        self.skip_blobs = skip_blobs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cancelled;
+    use reth_payload_builder::database::CachedReads;
+    use reth_primitives::{Block, ChainSpecBuilder, Header, B256, U256};
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_transaction_pool::test_utils::{testing_pool, MockTransaction, TestPool};
+    use std::time::Duration;
+
+    /// Builds a payload from the transactions of the pool with the given comparator.
+    fn build_ordered<F>(client: MockEthProvider, pool: TestPool, compare: F) -> Vec<B256>
+    where
+        F: Fn(
+                &ValidPoolTransaction<MockTransaction>,
+                &ValidPoolTransaction<MockTransaction>,
+            ) -> Ordering
+            + Send
+            + Sync
+            + Clone,
+    {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().shanghai_activated().build());
+        let parent = Block {
+            header: Header { number: 1, gas_limit: 30_000_000, ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+        client.add_block(parent.hash(), parent.clone().unseal());
+        let attributes = EthPayloadBuilderAttributes {
+            id: Default::default(),
+            parent: parent.hash(),
+            timestamp: 1,
+            suggested_fee_recipient: Address::random(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root: None,
+        };
+        let config =
+            PayloadConfig::new(Arc::new(parent), Default::default(), attributes, chain_spec);
+        let args = BuildArguments::new(
+            client,
+            pool,
+            CachedReads::default(),
+            config,
+            Cancelled::default(),
+            None,
+        );
+
+        let BuildOutcome::Better { payload, .. } =
+            OrderedPayloadBuilder::new(compare).try_build(args).unwrap()
+        else {
+            panic!("expected a better payload")
+        };
+        payload.block().body.iter().map(|tx| tx.hash()).collect()
+    }
+
+    #[tokio::test]
+    async fn fifo_and_gas_price_order() {
+        let client = MockEthProvider::default();
+        let pool = testing_pool();
+        let mut hashes = Vec::new();
+        for priority_fee in [1_000_000_000, 3_000_000_000, 2_000_000_000] {
+            let tx = MockTransaction::eip1559()
+                .with_gas_limit(21_000)
+                .with_max_fee(10_000_000_000)
+                .with_priority_fee(priority_fee);
+            client.add_account(tx.get_sender(), ExtendedAccount::new(0, U256::from(u64::MAX)));
+            hashes.push(tx.get_hash());
+            pool.add_external_transaction(tx).await.unwrap();
+            // distinct pool timestamps
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let fifo =
+            build_ordered(client.clone(), pool.clone(), |a, b| a.timestamp.cmp(&b.timestamp));
+        assert_eq!(fifo, hashes);
+
+        let by_gas_price = build_ordered(client, pool, |a, b| {
+            b.priority_fee_or_price().cmp(&a.priority_fee_or_price())
+        });
+        assert_eq!(by_gas_price, vec![hashes[1], hashes[2], hashes[0]]);
+    }
+}
@@ -0,0 +1,441 @@
+//! A minimal [PayloadBuilder] implementation.
+
+use crate::{
+    cached_payload_state, commit_withdrawals, compute_excess_blob_gas, is_better_payload,
+    pre_block_beacon_root_contract_call, recover_forced_top_of_block, transact, BuildArguments,
+    BuildOutcome, Cancelled, PayloadBuilder, PayloadConfig, WithdrawalsOutcome,
+};
+use reth_payload_builder::{
+    database::CachedReads, error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
+};
+use reth_primitives::{
+    constants::BEACON_NONCE, proofs, revm::env::tx_env_with_recovered, Block, Header,
+    IntoRecoveredTransaction, Receipt, Receipts, TransactionSignedEcRecovered,
+    EMPTY_OMMER_ROOT_HASH, U256,
+};
+use reth_provider::{BundleStateWithReceipts, StateProviderFactory};
+use reth_revm::database::StateProviderDatabase;
+use reth_transaction_pool::{
+    BestTransactions, EthPooledTransaction, PoolTransaction, TransactionPool, ValidPoolTransaction,
+};
+use revm::{
+    primitives::{EVMError, EnvWithHandlerCfg, ExecutionResult, ResultAndState},
+    DatabaseCommit,
+};
+use std::sync::Arc;
+use tracing::{debug, trace};
+
+/// A minimal [PayloadBuilder] that greedily includes the best transactions of the pool until the
+/// block is full.
+///
+/// Blob transactions are skipped, so the payload job can be driven end-to-end without the full
+/// Ethereum builder, for example in tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SimplePayloadBuilder;
+
+impl<Pool, Client> PayloadBuilder<Pool, Client> for SimplePayloadBuilder
+where
+    Client: StateProviderFactory,
+    Pool: TransactionPool,
+{
+    type Attributes = EthPayloadBuilderAttributes;
+    type BuiltPayload = EthBuiltPayload;
+
+    fn try_build(
+        &self,
+        args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
+    ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
+        let BuildArguments { client, pool, cached_reads, config, cancel, best_payload } = args;
+        build_simple_payload(
+            &client,
+            Some(pool.best_transactions()),
+            cached_reads,
+            config,
+            &cancel,
+            best_payload,
+        )
+    }
+
+    fn build_empty_payload(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+    ) -> Result<Self::BuiltPayload, PayloadBuilderError> {
+        let outcome = build_simple_payload::<EthPooledTransaction, _>(
+            client,
+            None,
+            CachedReads::default(),
+            config,
+            &Cancelled::default(),
+            None,
+        )?;
+        match outcome {
+            BuildOutcome::Better { payload, .. } | BuildOutcome::Empty { payload, .. } => {
+                Ok(payload)
+            }
+            _ => unreachable!("payloads without a best payload are always better"),
+        }
+    }
+}
+
+/// Builds a payload with the forced top of block transactions followed by the given pool
+/// transactions, in iteration order.
+///
+/// If no transactions are given, this builds an empty payload without any transaction.
+pub(crate) fn build_simple_payload<T, Client>(
+    client: &Client,
+    best_txs: Option<Box<dyn BestTransactions<Item = Arc<ValidPoolTransaction<T>>>>>,
+    mut cached_reads: CachedReads,
+    config: PayloadConfig<EthPayloadBuilderAttributes>,
+    cancel: &Cancelled,
+    best_payload: Option<EthBuiltPayload>,
+) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>
+where
+    Client: StateProviderFactory,
+    T: PoolTransaction,
+{
+    let state_provider = client.state_by_block_hash(config.parent_block.hash())?;
+    let state = StateProviderDatabase::new(&state_provider);
+    let mut db = cached_payload_state(&mut cached_reads, &state);
+    let extra_data = config.extra_data();
+    let block_number = config.block_number()?;
+    let PayloadConfig {
+        initialized_block_env,
+        initialized_cfg,
+        parent_block,
+        attributes,
+        chain_spec,
+        forced_top_of_block,
+        debug_bundle_state,
+        bundle_retention,
+        debug_tx_traces,
+        max_transactions,
+        ..
+    } = config;
+
+    debug!(target: "payload_builder", id=%attributes.id, parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building simple payload");
+    let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+    let base_fee = initialized_block_env.basefee.to::<u64>();
+
+    pre_block_beacon_root_contract_call(
+        &mut db,
+        &chain_spec,
+        block_number,
+        &initialized_cfg,
+        &initialized_block_env,
+        &attributes,
+    )?;
+
+    let mut cumulative_gas_used = 0;
+    let mut total_fees = U256::ZERO;
+    let mut executed_txs = Vec::new();
+    let mut receipts = Vec::new();
+
+    // executes the transaction and records its receipt and fees
+    let mut execute = |db: &mut _, tx: TransactionSignedEcRecovered| {
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            tx_env_with_recovered(&tx),
+        );
+        let result = transact(&mut *db, env, tx.hash, debug_tx_traces.as_ref());
+        let result = match commit(db, result) {
+            Ok(result) => result,
+            Err(err) => return Err(err),
+        };
+        let gas_used = result.gas_used();
+        cumulative_gas_used += gas_used;
+
+        #[allow(clippy::needless_update)] // side-effect of optimism fields
+        receipts.push(Some(Receipt {
+            tx_type: tx.tx_type(),
+            success: result.is_success(),
+            cumulative_gas_used,
+            logs: result.into_logs().into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }));
+
+        let miner_fee = tx
+            .effective_tip_per_gas(Some(base_fee))
+            .expect("fee is always valid; execution succeeded");
+        total_fees += U256::from(miner_fee) * U256::from(gas_used);
+        executed_txs.push(tx.into_signed());
+        Ok(cumulative_gas_used)
+    };
+
+    if let Some(mut best_txs) = best_txs {
+        // the forced transactions must be included in order before any pool transaction
+        let mut gas_used = 0;
+        for tx in recover_forced_top_of_block(forced_top_of_block, block_gas_limit)? {
+            gas_used = execute(&mut db, tx).map_err(PayloadBuilderError::EvmExecutionError)?;
+        }
+
+        let mut included = 0;
+        while let Some(pool_tx) = best_txs.next() {
+            if max_transactions.is_some_and(|max| included >= max) {
+                break
+            }
+            if gas_used + pool_tx.gas_limit() > block_gas_limit || pool_tx.is_eip4844() {
+                best_txs.mark_invalid(&pool_tx);
+                continue
+            }
+
+            if cancel.is_cancelled() {
+                return Ok(BuildOutcome::Cancelled)
+            }
+
+            match execute(&mut db, pool_tx.to_recovered_transaction()) {
+                Ok(cumulative_gas_used) => {
+                    gas_used = cumulative_gas_used;
+                    included += 1;
+                }
+                Err(EVMError::Transaction(err)) => {
+                    trace!(target: "payload_builder", %err, "skipping invalid transaction and its descendants");
+                    best_txs.mark_invalid(&pool_tx);
+                }
+                Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+            }
+        }
+    }
+
+    if !is_better_payload(best_payload.as_ref(), total_fees) {
+        return Ok(BuildOutcome::Aborted { fees: total_fees, cached_reads })
+    }
+
+    let WithdrawalsOutcome { withdrawals_root, withdrawals } =
+        commit_withdrawals(&mut db, &chain_spec, attributes.timestamp, attributes.withdrawals)?;
+
+    db.merge_transitions(bundle_retention);
+
+    let bundle = BundleStateWithReceipts::new(
+        db.take_bundle(),
+        Receipts::from_vec(vec![receipts]),
+        block_number,
+    );
+    let receipts_root = bundle.receipts_root_slow(block_number).expect("Number is in range");
+    let logs_bloom = bundle.block_logs_bloom(block_number).expect("Number is in range");
+    if let Some(hook) = &debug_bundle_state {
+        hook.call(&bundle);
+    }
+    let state_root = state_provider.state_root(bundle.state())?;
+    let transactions_root = proofs::calculate_transaction_root(&executed_txs);
+
+    // blob transactions are never included
+    let (blob_gas_used, excess_blob_gas) =
+        if chain_spec.is_cancun_active_at_timestamp(attributes.timestamp) {
+            (Some(0), Some(compute_excess_blob_gas(&chain_spec, &parent_block)))
+        } else {
+            (None, None)
+        };
+
+    let header = Header {
+        parent_hash: parent_block.hash(),
+        ommers_hash: EMPTY_OMMER_ROOT_HASH,
+        beneficiary: initialized_block_env.coinbase,
+        state_root,
+        transactions_root,
+        receipts_root,
+        withdrawals_root,
+        logs_bloom,
+        timestamp: attributes.timestamp,
+        mix_hash: attributes.prev_randao,
+        nonce: BEACON_NONCE,
+        base_fee_per_gas: Some(base_fee),
+        number: parent_block.number + 1,
+        gas_limit: block_gas_limit,
+        difficulty: U256::ZERO,
+        gas_used: cumulative_gas_used,
+        extra_data,
+        parent_beacon_block_root: attributes.parent_beacon_block_root,
+        blob_gas_used,
+        excess_blob_gas,
+    };
+
+    let is_empty = executed_txs.is_empty();
+    let block = Block { header, body: executed_txs, ommers: vec![], withdrawals };
+    let payload = EthBuiltPayload::new(attributes.id, block.seal_slow(), total_fees);
+
+    if is_empty {
+        return Ok(BuildOutcome::Empty { payload, cached_reads })
+    }
+    Ok(BuildOutcome::Better { payload, cached_reads })
+}
+
+/// Commits the state changes of the executed transaction.
+fn commit<DB, E>(
+    db: &mut DB,
+    res: Result<ResultAndState, EVMError<E>>,
+) -> Result<ExecutionResult, EVMError<E>>
+where
+    DB: DatabaseCommit,
+{
+    let ResultAndState { result, state } = res?;
+    db.commit(state);
+    Ok(result)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::BundleStateHook;
+    use reth_node_api::{BuiltPayload, PayloadKind};
+    use reth_primitives::{
+        Address, Bytes, ChainSpecBuilder, SealedBlock, TransactionKind, Withdrawal, Withdrawals,
+        B256,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_transaction_pool::{
+        noop::NoopTransactionPool,
+        test_utils::{testing_pool, MockTransaction, TestPool},
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// Returns a client that knows a parent block with a 30M gas limit, and the config of a
+    /// post-Shanghai payload on top of it.
+    pub(crate) fn mock_config() -> (MockEthProvider, PayloadConfig<EthPayloadBuilderAttributes>) {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().shanghai_activated().build());
+        let client = MockEthProvider::default();
+        let parent = Block {
+            header: Header { number: 1, gas_limit: 30_000_000, ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+        client.add_block(parent.hash(), parent.clone().unseal());
+
+        let attributes = EthPayloadBuilderAttributes {
+            id: Default::default(),
+            parent: parent.hash(),
+            timestamp: 1,
+            suggested_fee_recipient: Address::random(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root: None,
+        };
+        let config =
+            PayloadConfig::new(Arc::new(parent), Default::default(), attributes, chain_spec);
+        (client, config)
+    }
+
+    /// Adds the transaction to the pool and funds its sender.
+    pub(crate) async fn add_transaction(
+        client: &MockEthProvider,
+        pool: &TestPool,
+        tx: MockTransaction,
+    ) {
+        client.add_account(tx.get_sender(), ExtendedAccount::new(0, U256::from(u64::MAX)));
+        pool.add_external_transaction(tx).await.unwrap();
+    }
+
+    /// Returns the arguments of a build without cached reads and best payload.
+    pub(crate) fn build_args<Pool>(
+        client: MockEthProvider,
+        pool: Pool,
+        config: PayloadConfig<EthPayloadBuilderAttributes>,
+    ) -> BuildArguments<Pool, MockEthProvider, EthPayloadBuilderAttributes, EthBuiltPayload> {
+        BuildArguments::builder(client, pool, config).build()
+    }
+
+    #[tokio::test]
+    async fn builds_block_from_pool() {
+        let (client, config) = mock_config();
+        let pool = testing_pool();
+        let tx = MockTransaction::eip1559().with_gas_limit(21_000);
+        add_transaction(&client, &pool, tx.clone()).await;
+
+        let BuildOutcome::Better { payload, .. } =
+            SimplePayloadBuilder.try_build(build_args(client, pool, config)).unwrap()
+        else {
+            panic!("expected a better payload")
+        };
+        let block: &SealedBlock = payload.block();
+        assert_eq!(block.body.len(), 1);
+        assert_eq!(block.body[0].hash(), tx.get_hash());
+        assert_eq!(block.gas_used, 21_000);
+    }
+
+    #[test]
+    fn empty_payload_with_withdrawals() {
+        let (client, mut config) = mock_config();
+        let payload =
+            <SimplePayloadBuilder as PayloadBuilder<NoopTransactionPool, _>>::build_empty_payload(
+                &client,
+                config.clone(),
+            )
+            .unwrap();
+        assert_eq!(payload.kind(), PayloadKind::Empty);
+
+        config.attributes.withdrawals = Withdrawals::new(vec![
+            Withdrawal {
+                address: Address::random(),
+                amount: 1,
+                ..Default::default()
+            };
+            16
+        ]);
+        let payload =
+            <SimplePayloadBuilder as PayloadBuilder<NoopTransactionPool, _>>::build_empty_payload(
+                &client, config,
+            )
+            .unwrap();
+        assert!(payload.block().body.is_empty());
+        assert_eq!(payload.kind(), PayloadKind::EmptyWithWithdrawals);
+    }
+
+    #[tokio::test]
+    async fn block_env_timestamp_override() {
+        let (client, config) = mock_config();
+
+        // TIMESTAMP PUSH1 0 SSTORE STOP
+        let contract = Address::random();
+        client.add_account(
+            contract,
+            ExtendedAccount::new(0, U256::ZERO)
+                .with_bytecode(Bytes::from_static(&[0x42, 0x60, 0x00, 0x55, 0x00])),
+        );
+
+        let pool = testing_pool();
+        let tx = MockTransaction::eip1559()
+            .with_gas_limit(100_000)
+            .with_to(TransactionKind::Call(contract));
+        add_transaction(&client, &pool, tx).await;
+
+        let stored = Arc::new(Mutex::new(None));
+        let hook = {
+            let stored = stored.clone();
+            BundleStateHook::new(move |bundle| {
+                *stored.lock().unwrap() = bundle.storage(&contract, U256::ZERO);
+            })
+        };
+        let config = config.with_block_env_timestamp_override(42).with_debug_bundle_state(hook);
+
+        let BuildOutcome::Better { payload, .. } =
+            SimplePayloadBuilder.try_build(build_args(client, pool, config)).unwrap()
+        else {
+            panic!("expected a better payload")
+        };
+        assert_eq!(*stored.lock().unwrap(), Some(U256::from(42)));
+        assert_eq!(payload.block().timestamp, 1);
+    }
+
+    #[tokio::test]
+    async fn gas_limit_override() {
+        let (client, config) = mock_config();
+        let pool = testing_pool();
+        for gas_limit in [21_000, 50_000] {
+            add_transaction(&client, &pool, MockTransaction::eip1559().with_gas_limit(gas_limit))
+                .await;
+        }
+
+        let config = config.with_gas_limit(40_000);
+        assert_eq!(config.initialized_block_env.gas_limit, U256::from(40_000));
+
+        let BuildOutcome::Better { payload, .. } =
+            SimplePayloadBuilder.try_build(build_args(client, pool, config)).unwrap()
+        else {
+            panic!("expected a better payload")
+        };
+        // only the transaction that fits into the overridden gas limit is included
+        assert_eq!(payload.block().body.len(), 1);
+        assert_eq!(payload.block().gas_limit, 40_000);
+    }
+}
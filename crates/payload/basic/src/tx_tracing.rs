@@ -0,0 +1,52 @@
+//! Tracing of the transactions executed by payload builders.
+
+use reth_primitives::TxHash;
+use reth_revm::tracing::{TracingInspector, TracingInspectorConfig};
+use revm::{
+    inspector_handle_register,
+    primitives::{EVMError, EnvWithHandlerCfg, ResultAndState},
+    Database, Evm,
+};
+use std::{fmt, sync::Arc};
+
+/// A callback that inspects the call traces of a transaction executed by a builder.
+#[derive(Clone)]
+pub struct TxTraceHook(Arc<dyn Fn(TxHash, &TracingInspector) + Send + Sync>);
+
+impl TxTraceHook {
+    /// Creates a new hook from the given callback.
+    pub fn new(hook: impl Fn(TxHash, &TracingInspector) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Invokes the callback with the traces of the given transaction.
+    pub fn call(&self, tx_hash: TxHash, traces: &TracingInspector) {
+        (self.0)(tx_hash, traces)
+    }
+}
+
+impl fmt::Debug for TxTraceHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TxTraceHook").finish_non_exhaustive()
+    }
+}
+
+/// Executes the transaction of the given environment with a tracing inspector and invokes the hook
+/// with the traces, see [crate::transact].
+pub(crate) fn transact_traced<DB: Database>(
+    db: DB,
+    env: EnvWithHandlerCfg,
+    tx_hash: TxHash,
+    hook: &TxTraceHook,
+) -> Result<ResultAndState, EVMError<DB::Error>> {
+    let mut inspector = TracingInspector::new(TracingInspectorConfig::default_parity());
+    let res = Evm::builder()
+        .with_db(db)
+        .with_external_context(&mut inspector)
+        .with_env_with_handler_cfg(env)
+        .append_handler_register(inspector_handle_register)
+        .build()
+        .transact();
+    hook.call(tx_hash, &inspector);
+    res
+}
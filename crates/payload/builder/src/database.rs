@@ -1,12 +1,15 @@
 //! Database adapters for payload building.
 
+use reth_interfaces::provider::ProviderResult;
 use reth_primitives::{
+    revm::compat::into_revm_acc,
     revm_primitives::{
         db::{Database, DatabaseRef},
         AccountInfo, Address, Bytecode, B256,
     },
     U256,
 };
+use reth_provider::StateProvider;
 use std::{
     cell::RefCell,
     collections::{hash_map::Entry, HashMap},
@@ -59,6 +62,55 @@ impl CachedReads {
     ) {
         self.accounts.insert(address, CachedAccount { info: Some(info), storage });
     }
+
+    /// Returns the approximate heap size of the cached reads in bytes.
+    ///
+    /// This only accounts for the cached entries and the contract code, not for the allocation
+    /// overhead of the maps.
+    pub fn approx_size_bytes(&self) -> usize {
+        let accounts = self
+            .accounts
+            .values()
+            .map(|account| {
+                std::mem::size_of::<(Address, CachedAccount)>() +
+                    account.storage.len() * std::mem::size_of::<(U256, U256)>()
+            })
+            .sum::<usize>();
+        let contracts = self
+            .contracts
+            .values()
+            .map(|code| std::mem::size_of::<(B256, Bytecode)>() + code.bytecode.len())
+            .sum::<usize>();
+        let block_hashes = self.block_hashes.len() * std::mem::size_of::<(U256, B256)>();
+        accounts + contracts + block_hashes
+    }
+
+    /// Reads the given accounts and storage slots from the state provider and caches them.
+    ///
+    /// This loads the state a block is expected to touch ahead of the first build attempt.
+    /// Accounts and storage slots that are already cached are not read again, storage slots are
+    /// only cached for accounts that were loaded.
+    pub fn prewarm<P: StateProvider>(
+        &mut self,
+        provider: P,
+        accounts: &[Address],
+        storage: &[(Address, B256)],
+    ) -> ProviderResult<()> {
+        for address in accounts.iter().chain(storage.iter().map(|(address, _)| address)) {
+            if let Entry::Vacant(entry) = self.accounts.entry(*address) {
+                let account = provider.basic_account(*address)?;
+                entry.insert(CachedAccount::new(account.map(into_revm_acc)));
+            }
+        }
+        for (address, key) in storage {
+            let Some(account) = self.accounts.get_mut(address) else { continue };
+            if let Entry::Vacant(entry) = account.storage.entry(U256::from_be_bytes(key.0)) {
+                entry.insert(provider.storage(*address, *key)?.unwrap_or_default());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -160,3 +212,49 @@ impl CachedAccount {
         Self { info, storage: HashMap::new() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use revm::db::EmptyDB;
+
+    #[test]
+    fn prewarmed_reads_are_cached() {
+        let provider = MockEthProvider::default();
+        let address = Address::random();
+        let key = B256::with_last_byte(1);
+        provider.add_account(
+            address,
+            ExtendedAccount::new(1, U256::from(2)).extend_storage([(key, U256::from(3))]),
+        );
+
+        let mut cached = CachedReads::default();
+        cached.prewarm(&provider, &[address], &[(address, key)]).unwrap();
+
+        // reads are served from the cache, the underlying database is empty
+        let db = cached.as_db(EmptyDB::default());
+        let info = db.basic_ref(address).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.balance, U256::from(2));
+        assert_eq!(db.storage_ref(address, U256::from(1)).unwrap(), U256::from(3));
+    }
+
+    #[test]
+    fn repeated_reads_do_not_grow_cache() {
+        let address = Address::random();
+        let mut cached = CachedReads::default();
+        cached.insert_account(address, AccountInfo::default(), HashMap::default());
+        let size = cached.approx_size_bytes();
+
+        // reading the same account again is served from the cache
+        let db = cached.as_db(EmptyDB::default());
+        db.basic_ref(address).unwrap();
+        assert_eq!(cached.approx_size_bytes(), size);
+
+        // a new account grows the cache
+        let db = cached.as_db(EmptyDB::default());
+        db.basic_ref(Address::random()).unwrap();
+        assert!(cached.approx_size_bytes() > size);
+    }
+}
@@ -3,6 +3,7 @@
 use reth_interfaces::{provider::ProviderError, RethError};
 use reth_primitives::{revm_primitives::EVMError, B256};
 use reth_transaction_pool::BlobStoreError;
+use std::time::Duration;
 use tokio::sync::oneshot;
 
 /// Possible error variants during payload building.
@@ -11,6 +12,9 @@ pub enum PayloadBuilderError {
     /// Thrown when the parent block is missing.
     #[error("missing parent block {0}")]
     MissingParentBlock(B256),
+    /// Thrown when the latest block, used as parent if no parent was specified, is missing.
+    #[error("missing latest block")]
+    MissingLatestBlock,
     /// An oneshot channels has been closed.
     #[error("sender has been dropped")]
     ChannelClosed,
@@ -26,6 +30,45 @@ pub enum PayloadBuilderError {
     /// Thrown if the payload requests withdrawals before Shanghai activation.
     #[error("withdrawals set before Shanghai activation")]
     WithdrawalsBeforeShanghai,
+    /// Thrown if the block number of the block environment isn't the number of the parent block
+    /// plus one.
+    #[error("block number {got} doesn't follow the parent block number, expected {expected}")]
+    BlockNumberMismatch {
+        /// The number of the child block of the parent block.
+        expected: u64,
+        /// The number of the block environment.
+        got: u64,
+    },
+    /// Thrown if the withdrawal balance increments couldn't be applied to the state.
+    #[error("failed to apply withdrawal balance increments: {0}")]
+    WithdrawalBalanceIncrement(ProviderError),
+    /// Thrown if a build attempt didn't finish in time.
+    #[error("payload build timed out after {elapsed:?}")]
+    BuildTimedOut {
+        /// How long the build attempt ran.
+        elapsed: Duration,
+    },
+    /// Thrown if new jobs are rejected because too many jobs failed in a row.
+    #[error("payload building suspended after {failed_jobs} failed jobs")]
+    BuildingSuspended {
+        /// The number of jobs that failed in a row.
+        failed_jobs: u32,
+    },
+    /// Thrown if a forced top of block transaction can't be included, because its signature is
+    /// invalid or it's a blob transaction, whose sidecar isn't available.
+    #[error("invalid forced transaction {0}")]
+    InvalidForcedTransaction(B256),
+    /// Thrown if the forced top of block transactions don't fit into the block.
+    #[error("forced transactions need {gas_limit} gas, but only {remaining_gas} gas is left")]
+    ForcedTransactionsExceedGasLimit {
+        /// The sum of the gas limits of the forced transactions.
+        gas_limit: u64,
+        /// The gas that is left in the block.
+        remaining_gas: u64,
+    },
+    /// Thrown if new jobs are rejected because the payload builder is shut down.
+    #[error("payload builder is shut down")]
+    Shutdown,
     /// Any other payload building errors.
     #[error(transparent)]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -39,6 +82,17 @@ impl PayloadBuilderError {
     {
         PayloadBuilderError::Other(Box::new(error))
     }
+
+    /// Returns true if the error is transient and a subsequent build attempt may succeed.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, PayloadBuilderError::BuildTimedOut { .. })
+    }
+
+    /// Returns true if the error is likely deterministic, like a corrupted database, so that
+    /// subsequent build attempts on the same state are expected to fail the same way.
+    pub fn is_potentially_fatal(&self) -> bool {
+        matches!(self, PayloadBuilderError::WithdrawalBalanceIncrement(_))
+    }
 }
 
 impl From<ProviderError> for PayloadBuilderError {
@@ -1,3 +1,4 @@
+use crate::{PayloadId, PayloadJobCancelReason};
 use futures_util::Stream;
 use reth_node_api::EngineTypes;
 use tokio::sync::broadcast;
@@ -16,6 +17,8 @@ pub enum Events<Engine: EngineTypes> {
     /// Triggered by the CL whenever it asks for an execution payload.
     /// This event is only thrown if the CL is a validator.
     BuiltPayload(Engine::BuiltPayload),
+    /// The payload job with the given id was dropped before it was resolved.
+    Cancelled(PayloadId, PayloadJobCancelReason),
 }
 
 /// Represents a receiver for various payload events.
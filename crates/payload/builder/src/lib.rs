@@ -116,5 +116,8 @@ pub mod test_utils;
 
 pub use payload::{EthBuiltPayload, EthPayloadBuilderAttributes};
 pub use reth_rpc_types::engine::PayloadId;
-pub use service::{PayloadBuilderHandle, PayloadBuilderService, PayloadStore};
+pub use service::{
+    PayloadBuilderHandle, PayloadBuilderService, PayloadJobCancelReason, PayloadStore,
+    SupersededPayloadJobs,
+};
 pub use traits::{KeepPayloadJobAlive, PayloadJob, PayloadJobGenerator};
@@ -15,6 +15,8 @@ pub(crate) struct PayloadBuilderServiceMetrics {
     pub(crate) initiated_jobs: Counter,
     /// Total number of failed jobs
     pub(crate) failed_jobs: Counter,
+    /// Total number of jobs cancelled because a newer job for the same parent was created
+    pub(crate) superseded_jobs: Counter,
     /// Coinbase revenue for best payloads
     pub(crate) best_revenue: Gauge,
     /// Current block returned as the best payload
@@ -34,6 +36,10 @@ impl PayloadBuilderServiceMetrics {
         self.failed_jobs.increment(1);
     }
 
+    pub(crate) fn inc_superseded_jobs(&self) {
+        self.superseded_jobs.increment(1);
+    }
+
     pub(crate) fn set_active_jobs(&self, value: usize) {
         self.active_jobs.set(value as f64)
     }
@@ -12,6 +12,7 @@ use crate::{
 };
 use futures_util::{future::FutureExt, Stream, StreamExt};
 use reth_node_api::{BuiltPayload, EngineTypes, PayloadBuilderAttributes};
+use reth_primitives::B256;
 use reth_provider::CanonStateNotification;
 use reth_rpc_types::engine::PayloadId;
 use std::{
@@ -195,6 +196,26 @@ where
     }
 }
 
+/// How the [PayloadBuilderService] handles jobs that are superseded by a job for the same parent
+/// with a newer timestamp.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SupersededPayloadJobs {
+    /// Keep building the superseded jobs.
+    #[default]
+    Keep,
+    /// Drop the superseded jobs, which cancels their builds.
+    Cancel,
+}
+
+/// Why the [PayloadBuilderService] dropped a payload job before it was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PayloadJobCancelReason {
+    /// A job for the same parent with a newer timestamp was created, see
+    /// [SupersededPayloadJobs::Cancel].
+    Superseded,
+}
+
 /// A service that manages payload building tasks.
 ///
 /// This type is an endless future that manages the building of payloads.
@@ -225,6 +246,10 @@ where
     chain_events: St,
     /// Payload events handler, used to broadcast and subscribe to payload events.
     payload_events: broadcast::Sender<Events<Engine>>,
+    /// Whether a request for a payload that is already being built replaces its job.
+    replace_duplicate_jobs: bool,
+    /// How jobs that are superseded by a newer job for the same parent are handled.
+    superseded_jobs: SupersededPayloadJobs,
 }
 
 const PAYLOAD_EVENTS_BUFFER_SIZE: usize = 20;
@@ -255,12 +280,50 @@ where
             metrics: Default::default(),
             chain_events,
             payload_events,
+            replace_duplicate_jobs: false,
+            superseded_jobs: SupersededPayloadJobs::default(),
         };
 
         let handle = service.handle();
         (service, handle)
     }
 
+    /// Sets whether a request for a payload that is already being built drops the job in progress
+    /// and creates a new one.
+    ///
+    /// By default the job in progress is kept and its identifier is returned, so the generator
+    /// isn't asked for a second job of the same payload.
+    pub fn with_replace_duplicate_jobs(mut self, replace_duplicate_jobs: bool) -> Self {
+        self.replace_duplicate_jobs = replace_duplicate_jobs;
+        self
+    }
+
+    /// Sets how jobs are handled that are superseded by a new job for the same parent with a newer
+    /// timestamp.
+    ///
+    /// Defaults to [SupersededPayloadJobs::Keep].
+    pub fn with_superseded_jobs(mut self, superseded_jobs: SupersededPayloadJobs) -> Self {
+        self.superseded_jobs = superseded_jobs;
+        self
+    }
+
+    /// Drops all jobs that build on the given parent with an older timestamp.
+    fn cancel_superseded_jobs(&mut self, parent: B256, timestamp: u64) {
+        self.payload_jobs.retain(|(job, id)| {
+            let superseded = job
+                .payload_attributes()
+                .is_ok_and(|attr| attr.parent() == parent && attr.timestamp() < timestamp);
+            if superseded {
+                let reason = PayloadJobCancelReason::Superseded;
+                debug!(%id, %parent, ?reason, "Payload job superseded, cancelling.");
+                self.metrics.inc_superseded_jobs();
+                self.payload_events.send(Events::Cancelled(*id, reason)).ok();
+            }
+            !superseded
+        });
+        self.metrics.set_active_jobs(self.payload_jobs.len());
+    }
+
     /// Notifies the service on new attribute event.
     pub fn on_new_attributes(
         &self,
@@ -412,11 +475,19 @@ where
                         let id = attr.payload_id();
                         let mut res = Ok(id);
 
-                        if this.contains_payload(id) {
+                        if this.contains_payload(id) && !this.replace_duplicate_jobs {
                             debug!(%id, parent = %attr.parent(), "Payload job already in progress, ignoring.");
                         } else {
+                            if this.contains_payload(id) {
+                                debug!(%id, parent = %attr.parent(), "Payload job already in progress, replacing.");
+                                this.payload_jobs.retain(|(_, job_id)| *job_id != id);
+                            }
+
                             // no job for this payload yet, create one
                             let parent = attr.parent();
+                            if this.superseded_jobs == SupersededPayloadJobs::Cancel {
+                                this.cancel_superseded_jobs(parent, attr.timestamp());
+                            }
                             match this.generator.new_payload_job(attr) {
                                 Ok(job) => {
                                     info!(%id, %parent, "New payload job created");
@@ -503,3 +574,137 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{TestPayloadJob, TestPayloadJobGenerator},
+        EthBuiltPayload, EthPayloadBuilderAttributes,
+    };
+    use reth_node_api::{
+        validate_version_specific_fields, EngineApiMessageVersion, EngineObjectValidationError,
+        PayloadOrAttributes,
+    };
+    use reth_primitives::{ChainSpec, B256};
+    use reth_rpc_types::{
+        engine::{ExecutionPayloadEnvelopeV2, ExecutionPayloadEnvelopeV3, PayloadAttributes},
+        ExecutionPayloadV1,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+    struct TestEngineTypes;
+
+    impl EngineTypes for TestEngineTypes {
+        type PayloadAttributes = PayloadAttributes;
+        type PayloadBuilderAttributes = EthPayloadBuilderAttributes;
+        type BuiltPayload = EthBuiltPayload;
+        type ExecutionPayloadV1 = ExecutionPayloadV1;
+        type ExecutionPayloadV2 = ExecutionPayloadEnvelopeV2;
+        type ExecutionPayloadV3 = ExecutionPayloadEnvelopeV3;
+
+        fn validate_version_specific_fields(
+            chain_spec: &ChainSpec,
+            version: EngineApiMessageVersion,
+            payload_or_attrs: PayloadOrAttributes<'_, PayloadAttributes>,
+        ) -> Result<(), EngineObjectValidationError> {
+            validate_version_specific_fields(chain_spec, version, payload_or_attrs)
+        }
+    }
+
+    /// A [TestPayloadJobGenerator] that counts the created jobs.
+    #[derive(Debug, Default)]
+    struct CountingGenerator {
+        jobs: Arc<AtomicUsize>,
+    }
+
+    impl PayloadJobGenerator for CountingGenerator {
+        type Job = TestPayloadJob;
+
+        fn new_payload_job(
+            &self,
+            attr: EthPayloadBuilderAttributes,
+        ) -> Result<Self::Job, PayloadBuilderError> {
+            self.jobs.fetch_add(1, Ordering::Relaxed);
+            TestPayloadJobGenerator::default().new_payload_job(attr)
+        }
+    }
+
+    fn attributes() -> EthPayloadBuilderAttributes {
+        EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent: B256::ZERO,
+            timestamp: 0,
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root: None,
+        }
+    }
+
+    /// Requests the same payload twice and returns the number of created jobs.
+    async fn duplicate_requests(replace_duplicate_jobs: bool) -> usize {
+        let generator = CountingGenerator::default();
+        let jobs = Arc::clone(&generator.jobs);
+        let (service, handle) = PayloadBuilderService::<_, _, TestEngineTypes>::new(
+            generator,
+            futures_util::stream::empty(),
+        );
+        tokio::spawn(service.with_replace_duplicate_jobs(replace_duplicate_jobs));
+
+        let attr = attributes();
+        assert_eq!(handle.new_payload(attr.clone()).await.unwrap(), attr.id);
+        assert_eq!(handle.new_payload(attr.clone()).await.unwrap(), attr.id);
+        assert!(handle.payload_attributes(attr.id).await.is_some());
+        jobs.load(Ordering::Relaxed)
+    }
+
+    #[tokio::test]
+    async fn duplicate_job_is_kept() {
+        assert_eq!(duplicate_requests(false).await, 1);
+    }
+
+    #[tokio::test]
+    async fn duplicate_job_is_replaced() {
+        assert_eq!(duplicate_requests(true).await, 2);
+    }
+
+    #[tokio::test]
+    async fn superseded_job_is_cancelled() {
+        let (service, handle) = PayloadBuilderService::<_, _, TestEngineTypes>::new(
+            CountingGenerator::default(),
+            futures_util::stream::empty(),
+        );
+        tokio::spawn(service.with_superseded_jobs(SupersededPayloadJobs::Cancel));
+
+        let older = attributes();
+        let newer = EthPayloadBuilderAttributes {
+            id: PayloadId::new([2; 8]),
+            timestamp: 1,
+            ..attributes()
+        };
+        let other_parent = EthPayloadBuilderAttributes {
+            id: PayloadId::new([3; 8]),
+            parent: B256::with_last_byte(1),
+            ..attributes()
+        };
+        let mut events = handle.subscribe().await.unwrap().into_stream();
+        handle.new_payload(older.clone()).await.unwrap();
+        handle.new_payload(other_parent.clone()).await.unwrap();
+        handle.new_payload(newer.clone()).await.unwrap();
+
+        let Some(Ok(Events::Cancelled(id, reason))) = events.next().await else {
+            panic!("expected a cancelled job event")
+        };
+        assert_eq!(id, older.id);
+        assert_eq!(reason, PayloadJobCancelReason::Superseded);
+
+        assert!(handle.payload_attributes(older.id).await.is_none());
+        assert!(handle.payload_attributes(newer.id).await.is_some());
+        assert!(handle.payload_attributes(other_parent.id).await.is_some());
+    }
+}
@@ -10,28 +10,29 @@
 #![allow(clippy::useless_let_if_seq)]
 
 use reth_basic_payload_builder::{
-    commit_withdrawals, is_better_payload, pre_block_beacon_root_contract_call, BuildArguments,
-    BuildOutcome, PayloadBuilder, PayloadConfig, WithdrawalsOutcome,
+    cached_payload_state, commit_withdrawals, compute_excess_blob_gas, is_better_payload,
+    parent_state_provider, pre_block_beacon_root_contract_call, recover_forced_top_of_block,
+    transact, BuildArguments, BuildOutcome, PayloadBuilder, PayloadConfig, WithdrawalsOutcome,
 };
 use reth_payload_builder::{
-    error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
+    database::CachedReads, error::PayloadBuilderError, EthBuiltPayload, EthPayloadBuilderAttributes,
 };
 use reth_primitives::{
     constants::{
         eip4844::MAX_DATA_GAS_PER_BLOCK, BEACON_NONCE, EMPTY_RECEIPTS, EMPTY_TRANSACTIONS,
     },
-    eip4844::calculate_excess_blob_gas,
     proofs,
     revm::env::tx_env_with_recovered,
     Block, Header, IntoRecoveredTransaction, Receipt, Receipts, EMPTY_OMMER_ROOT_HASH, U256,
 };
-use reth_provider::{BundleStateWithReceipts, StateProviderFactory};
+use reth_provider::{
+    BundleStateWithReceipts, ProviderError, StateProviderBox, StateProviderFactory,
+};
 use reth_revm::database::StateProviderDatabase;
 use reth_transaction_pool::{BestTransactionsAttributes, TransactionPool};
 use revm::{
-    db::states::bundle_state::BundleRetention,
     primitives::{EVMError, EnvWithHandlerCfg, InvalidTransaction, ResultAndState},
-    DatabaseCommit, State,
+    Database, DatabaseCommit, State,
 };
 use tracing::{debug, trace, warn};
 
@@ -60,31 +61,77 @@ where
         client: &Client,
         config: PayloadConfig<Self::Attributes>,
     ) -> Result<EthBuiltPayload, PayloadBuilderError> {
-        let extra_data = config.extra_data();
-        let PayloadConfig {
-            initialized_block_env,
-            parent_block,
-            attributes,
-            chain_spec,
-            initialized_cfg,
-            ..
-        } = config;
-
-        debug!(target: "payload_builder", parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building empty payload");
-
-        let state = client.state_by_block_hash(parent_block.hash()).map_err(|err| {
-                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to get state for empty payload");
-                err
-            })?;
-        let mut db = State::builder()
-            .with_database_boxed(Box::new(StateProviderDatabase::new(&state)))
-            .with_bundle_update()
-            .build();
+        build_empty_payload_on_parent(client, config, None)
+    }
 
-        let base_fee = initialized_block_env.basefee.to::<u64>();
-        let block_number = initialized_block_env.number.to::<u64>();
-        let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+    fn build_empty_payload_with_cached_reads(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+        cached_reads: &mut CachedReads,
+    ) -> Result<EthBuiltPayload, PayloadBuilderError> {
+        build_empty_payload_on_parent(client, config, Some(cached_reads))
+    }
+}
 
+/// Builds the empty payload of the given config on the state of its parent block.
+///
+/// The state is read through the given [CachedReads], if any, see [cached_payload_state].
+fn build_empty_payload_on_parent<Client: StateProviderFactory>(
+    client: &Client,
+    config: PayloadConfig<EthPayloadBuilderAttributes>,
+    cached_reads: Option<&mut CachedReads>,
+) -> Result<EthBuiltPayload, PayloadBuilderError> {
+    let block_number = config.block_number().map_err(|err| {
+        warn!(target: "payload_builder", %err, "inconsistent block number for empty payload");
+        err
+    })?;
+
+    debug!(target: "payload_builder", parent_hash = ?config.parent_block.hash(), parent_number = config.parent_block.number, "building empty payload");
+
+    let state = parent_state_provider(client, &config.parent_block, config.latest_state_fallback)
+        .map_err(|err| {
+            warn!(target: "payload_builder", parent_hash=%config.parent_block.hash(), %err, "failed to get state for empty payload");
+            err
+        })?;
+    let state_db = StateProviderDatabase::new(&state);
+    match cached_reads {
+        Some(cached_reads) => {
+            let db = cached_payload_state(cached_reads, &state_db);
+            build_empty_payload_on_state(db, &state, config, block_number)
+        }
+        None => {
+            let db = State::builder().with_database_ref(&state_db).with_bundle_update().build();
+            build_empty_payload_on_state(db, &state, config, block_number)
+        }
+    }
+}
+
+/// Builds the empty payload of the given config with the given database of the parent state.
+///
+/// The `state` provider of the parent block is used to compute the state root.
+fn build_empty_payload_on_state<DB: Database<Error = ProviderError>>(
+    mut db: State<DB>,
+    state: &StateProviderBox,
+    config: PayloadConfig<EthPayloadBuilderAttributes>,
+    block_number: u64,
+) -> Result<EthBuiltPayload, PayloadBuilderError> {
+    let extra_data = config.extra_data();
+    let PayloadConfig {
+        initialized_block_env,
+        parent_block,
+        attributes,
+        chain_spec,
+        initialized_cfg,
+        debug_bundle_state,
+        bundle_retention,
+        ..
+    } = config;
+
+    let base_fee = initialized_block_env.basefee.to::<u64>();
+    let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+
+    let parent_beacon_block_root = if chain_spec.is_cancun_active_at_timestamp(attributes.timestamp)
+    {
         // apply eip-4788 pre block contract call
         pre_block_beacon_root_contract_call(
                 &mut db,
@@ -97,69 +144,75 @@ where
                 warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to apply beacon root contract call for empty payload");
                 err
             })?;
+        attributes.parent_beacon_block_root
+    } else {
+        // the parent beacon block root must not be set before cancun
+        if let Some(root) = attributes.parent_beacon_block_root {
+            warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %root, "ignoring parent beacon block root before cancun for empty payload");
+        }
+        None
+    };
 
-        let WithdrawalsOutcome { withdrawals_root, withdrawals } =
-                commit_withdrawals(&mut db, &chain_spec, attributes.timestamp, attributes.withdrawals.clone()).map_err(|err| {
-                    warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to commit withdrawals for empty payload");
-                    err
-                })?;
-
-        // merge all transitions into bundle state, this would apply the withdrawal balance
-        // changes and 4788 contract call
-        db.merge_transitions(BundleRetention::PlainState);
-
-        // calculate the state root
-        let bundle_state = db.take_bundle();
-        let state_root = state.state_root(&bundle_state).map_err(|err| {
-                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to calculate state root for empty payload");
+    let WithdrawalsOutcome { withdrawals_root, withdrawals } =
+            commit_withdrawals(&mut db, &chain_spec, attributes.timestamp, attributes.withdrawals.clone()).map_err(|err| {
+                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to commit withdrawals for empty payload");
                 err
             })?;
 
-        let mut excess_blob_gas = None;
-        let mut blob_gas_used = None;
-
-        if chain_spec.is_cancun_active_at_timestamp(attributes.timestamp) {
-            excess_blob_gas = if chain_spec.is_cancun_active_at_timestamp(parent_block.timestamp) {
-                let parent_excess_blob_gas = parent_block.excess_blob_gas.unwrap_or_default();
-                let parent_blob_gas_used = parent_block.blob_gas_used.unwrap_or_default();
-                Some(calculate_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used))
-            } else {
-                // for the first post-fork block, both parent.blob_gas_used and
-                // parent.excess_blob_gas are evaluated as 0
-                Some(calculate_excess_blob_gas(0, 0))
-            };
+    // merge all transitions into bundle state, this would apply the withdrawal balance
+    // changes and 4788 contract call
+    db.merge_transitions(bundle_retention);
 
-            blob_gas_used = Some(0);
-        }
+    // calculate the state root
+    let bundle_state = db.take_bundle();
+    if let Some(hook) = &debug_bundle_state {
+        hook.call(&BundleStateWithReceipts::new(
+            bundle_state.clone(),
+            Receipts::from_vec(vec![Vec::new()]),
+            block_number,
+        ));
+    }
+    let state_root = state.state_root(&bundle_state).map_err(|err| {
+            warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to calculate state root for empty payload");
+            err
+        })?;
 
-        let header = Header {
-            parent_hash: parent_block.hash(),
-            ommers_hash: EMPTY_OMMER_ROOT_HASH,
-            beneficiary: initialized_block_env.coinbase,
-            state_root,
-            transactions_root: EMPTY_TRANSACTIONS,
-            withdrawals_root,
-            receipts_root: EMPTY_RECEIPTS,
-            logs_bloom: Default::default(),
-            timestamp: attributes.timestamp,
-            mix_hash: attributes.prev_randao,
-            nonce: BEACON_NONCE,
-            base_fee_per_gas: Some(base_fee),
-            number: parent_block.number + 1,
-            gas_limit: block_gas_limit,
-            difficulty: U256::ZERO,
-            gas_used: 0,
-            extra_data,
-            blob_gas_used,
-            excess_blob_gas,
-            parent_beacon_block_root: attributes.parent_beacon_block_root,
-        };
+    let mut excess_blob_gas = None;
+    let mut blob_gas_used = None;
 
-        let block = Block { header, body: vec![], ommers: vec![], withdrawals };
-        let sealed_block = block.seal_slow();
+    if chain_spec.is_cancun_active_at_timestamp(attributes.timestamp) {
+        excess_blob_gas = Some(compute_excess_blob_gas(&chain_spec, &parent_block));
 
-        Ok(EthBuiltPayload::new(attributes.payload_id(), sealed_block, U256::ZERO))
+        blob_gas_used = Some(0);
     }
+
+    let header = Header {
+        parent_hash: parent_block.hash(),
+        ommers_hash: EMPTY_OMMER_ROOT_HASH,
+        beneficiary: initialized_block_env.coinbase,
+        state_root,
+        transactions_root: EMPTY_TRANSACTIONS,
+        withdrawals_root,
+        receipts_root: EMPTY_RECEIPTS,
+        logs_bloom: Default::default(),
+        timestamp: attributes.timestamp,
+        mix_hash: attributes.prev_randao,
+        nonce: BEACON_NONCE,
+        base_fee_per_gas: Some(base_fee),
+        number: parent_block.number + 1,
+        gas_limit: block_gas_limit,
+        difficulty: U256::ZERO,
+        gas_used: 0,
+        extra_data,
+        blob_gas_used,
+        excess_blob_gas,
+        parent_beacon_block_root,
+    };
+
+    let block = Block { header, body: vec![], ommers: vec![], withdrawals };
+    let sealed_block = block.seal_slow();
+
+    Ok(EthBuiltPayload::new(attributes.payload_id(), sealed_block, U256::ZERO))
 }
 
 /// Constructs an Ethereum transaction payload using the best transactions from the pool.
@@ -179,8 +232,7 @@ where
 
     let state_provider = client.state_by_block_hash(config.parent_block.hash())?;
     let state = StateProviderDatabase::new(&state_provider);
-    let mut db =
-        State::builder().with_database_ref(cached_reads.as_db(&state)).with_bundle_update().build();
+    let mut db = cached_payload_state(&mut cached_reads, &state);
     let extra_data = config.extra_data();
     let PayloadConfig {
         initialized_block_env,
@@ -188,6 +240,11 @@ where
         parent_block,
         attributes,
         chain_spec,
+        forced_top_of_block,
+        debug_bundle_state,
+        bundle_retention,
+        debug_tx_traces,
+        max_transactions,
         ..
     } = config;
 
@@ -219,7 +276,55 @@ where
     )?;
 
     let mut receipts = Vec::new();
+
+    // the forced transactions are included in order before any pool transaction
+    for tx in recover_forced_top_of_block(forced_top_of_block, block_gas_limit)? {
+        // check if the job was cancelled, if so we can exit early
+        if cancel.is_cancelled() {
+            return Ok(BuildOutcome::Cancelled)
+        }
+
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            tx_env_with_recovered(&tx),
+        );
+
+        let res = transact(&mut db, env, tx.hash, debug_tx_traces.as_ref());
+        // a forced transaction that can't be executed fails the build, since it must be included
+        let ResultAndState { result, state } =
+            res.map_err(PayloadBuilderError::EvmExecutionError)?;
+        db.commit(state);
+
+        let gas_used = result.gas_used();
+        cumulative_gas_used += gas_used;
+
+        #[allow(clippy::needless_update)] // side-effect of optimism fields
+        receipts.push(Some(Receipt {
+            tx_type: tx.tx_type(),
+            success: result.is_success(),
+            cumulative_gas_used,
+            logs: result.into_logs().into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }));
+
+        let miner_fee = tx
+            .effective_tip_per_gas(Some(base_fee))
+            .expect("fee is always valid; execution succeeded");
+        total_fees += U256::from(miner_fee) * U256::from(gas_used);
+
+        executed_txs.push(tx.into_signed());
+    }
+
+    // the forced transactions don't count towards the maximum number of transactions
+    let forced_txs = executed_txs.len();
     while let Some(pool_tx) = best_txs.next() {
+        // stop once the configured number of transactions is included
+        if max_transactions.is_some_and(|max| executed_txs.len() - forced_txs >= max) {
+            trace!(target: "payload_builder", ?max_transactions, "reached the maximum number of transactions");
+            break
+        }
+
         // ensure we still have capacity for this transaction
         if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
             // we can't fit this transaction into the block, so we need to mark it as invalid
@@ -253,16 +358,14 @@ where
         }
 
         // Configure the environment for the block.
-        let mut evm = revm::Evm::builder()
-            .with_db(&mut db)
-            .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
-                initialized_cfg.clone(),
-                initialized_block_env.clone(),
-                tx_env_with_recovered(&tx),
-            ))
-            .build();
-
-        let ResultAndState { result, state } = match evm.transact() {
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            tx_env_with_recovered(&tx),
+        );
+
+        let res = transact(&mut db, env, tx.hash, debug_tx_traces.as_ref());
+        let ResultAndState { result, state } = match res {
             Ok(res) => res,
             Err(err) => {
                 match err {
@@ -286,8 +389,6 @@ where
                 }
             }
         };
-        // drop evm so db is released.
-        drop(evm);
         // commit changes
         db.commit(state);
 
@@ -338,7 +439,7 @@ where
 
     // merge all transitions into bundle state, this would apply the withdrawal balance changes
     // and 4788 contract call
-    db.merge_transitions(BundleRetention::PlainState);
+    db.merge_transitions(bundle_retention);
 
     let bundle = BundleStateWithReceipts::new(
         db.take_bundle(),
@@ -348,6 +449,10 @@ where
     let receipts_root = bundle.receipts_root_slow(block_number).expect("Number is in range");
     let logs_bloom = bundle.block_logs_bloom(block_number).expect("Number is in range");
 
+    if let Some(hook) = &debug_bundle_state {
+        hook.call(&bundle);
+    }
+
     // calculate the state root
     let state_root = state_provider.state_root(bundle.state())?;
 
@@ -366,15 +471,7 @@ where
             executed_txs.iter().filter(|tx| tx.is_eip4844()).map(|tx| tx.hash).collect(),
         )?;
 
-        excess_blob_gas = if chain_spec.is_cancun_active_at_timestamp(parent_block.timestamp) {
-            let parent_excess_blob_gas = parent_block.excess_blob_gas.unwrap_or_default();
-            let parent_blob_gas_used = parent_block.blob_gas_used.unwrap_or_default();
-            Some(calculate_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used))
-        } else {
-            // for the first post-fork block, both parent.blob_gas_used and
-            // parent.excess_blob_gas are evaluated as 0
-            Some(calculate_excess_blob_gas(0, 0))
-        };
+        excess_blob_gas = Some(compute_excess_blob_gas(&chain_spec, &parent_block));
 
         blob_gas_used = Some(sum_blob_gas_used);
     }
@@ -403,6 +500,7 @@ where
     };
 
     // seal the block
+    let is_empty = executed_txs.is_empty();
     let block = Block { header, body: executed_txs, ommers: vec![], withdrawals };
 
     let sealed_block = block.seal_slow();
@@ -413,5 +511,264 @@ where
     // extend the payload with the blob sidecars from the executed txs
     payload.extend_sidecars(blob_sidecars);
 
+    if is_empty {
+        // nothing to include, the job can serve this as its empty payload
+        return Ok(BuildOutcome::Empty { payload, cached_reads })
+    }
     Ok(BuildOutcome::Better { payload, cached_reads })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_basic_payload_builder::{BundleStateHook, Cancelled};
+    use reth_payload_builder::PayloadId;
+    use reth_primitives::{
+        constants::GWEI_TO_WEI, sign_message, Address, Bytes, ChainSpec, ChainSpecBuilder,
+        Hardfork, Transaction, TransactionKind, TransactionSigned, TxLegacy, Withdrawal,
+        Withdrawals, B256,
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_transaction_pool::noop::NoopTransactionPool;
+    use revm::{
+        db::{states::bundle_state::BundleRetention, EmptyDB},
+        primitives::AccountInfo,
+        DatabaseRef,
+    };
+    use std::{
+        sync::{Arc, Mutex},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    fn mock_config(
+        chain_spec: Arc<ChainSpec>,
+        parent_beacon_block_root: Option<B256>,
+    ) -> PayloadConfig<EthPayloadBuilderAttributes> {
+        let parent = Block {
+            header: Header { number: 1, gas_limit: 30_000_000, ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+        let attributes = EthPayloadBuilderAttributes {
+            id: PayloadId::new([1; 8]),
+            parent: parent.hash(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            suggested_fee_recipient: Default::default(),
+            prev_randao: B256::ZERO,
+            withdrawals: Default::default(),
+            parent_beacon_block_root,
+        };
+        PayloadConfig::new(Arc::new(parent), Bytes::default(), attributes, chain_spec)
+    }
+
+    fn build_empty_payload(
+        config: PayloadConfig<EthPayloadBuilderAttributes>,
+    ) -> Result<EthBuiltPayload, PayloadBuilderError> {
+        <EthereumPayloadBuilder as PayloadBuilder<NoopTransactionPool, MockEthProvider>>::build_empty_payload(
+            &MockEthProvider::default(),
+            config,
+        )
+    }
+
+    /// Returns a signed transfer with the given nonce and gas limit, and funds its sender.
+    fn funded_transfer(client: &MockEthProvider, nonce: u64, gas_limit: u64) -> TransactionSigned {
+        let transaction = Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            nonce,
+            gas_price: GWEI_TO_WEI as u128,
+            gas_limit,
+            to: TransactionKind::Call(Address::with_last_byte(2)),
+            ..Default::default()
+        });
+        let signature =
+            sign_message(B256::with_last_byte(1), transaction.signature_hash()).unwrap();
+        let tx = TransactionSigned::from_transaction_and_signature(transaction, signature);
+        client.add_account(
+            tx.recover_signer().unwrap(),
+            ExtendedAccount::new(0, U256::from(u64::MAX)),
+        );
+        tx
+    }
+
+    fn try_build(
+        client: MockEthProvider,
+        config: PayloadConfig<EthPayloadBuilderAttributes>,
+    ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError> {
+        default_ethereum_payload_builder(BuildArguments::new(
+            client,
+            NoopTransactionPool::default(),
+            CachedReads::default(),
+            config,
+            Cancelled::default(),
+            None,
+        ))
+    }
+
+    #[test]
+    fn forced_top_of_block_is_included_first() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let client = MockEthProvider::default();
+        let forced = vec![funded_transfer(&client, 0, 21_000), funded_transfer(&client, 1, 21_000)];
+        let config = mock_config(chain_spec, None).with_forced_top_of_block(forced.clone());
+
+        let BuildOutcome::Better { payload, .. } = try_build(client, config).unwrap() else {
+            panic!("expected a better payload")
+        };
+        assert_eq!(payload.block().body, forced);
+        assert_eq!(payload.block().gas_used, 42_000);
+    }
+
+    #[test]
+    fn forced_top_of_block_exceeding_gas_limit_is_rejected() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let client = MockEthProvider::default();
+        let forced = vec![funded_transfer(&client, 0, 30_000_001)];
+        let config = mock_config(chain_spec, None).with_forced_top_of_block(forced);
+
+        let err = try_build(client, config).unwrap_err();
+        assert!(matches!(
+            err,
+            PayloadBuilderError::ForcedTransactionsExceedGasLimit {
+                gas_limit: 30_000_001,
+                remaining_gas: 30_000_000
+            }
+        ));
+    }
+
+    #[test]
+    fn empty_payload_drops_parent_beacon_block_root_before_cancun() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let config = mock_config(chain_spec, Some(B256::random()));
+
+        let payload = build_empty_payload(config).unwrap();
+        assert_eq!(payload.block().parent_beacon_block_root, None);
+    }
+
+    #[test]
+    fn empty_payload_rejects_mismatched_block_number() {
+        let mut config = mock_config(Arc::new(ChainSpecBuilder::mainnet().build()), None);
+        config.initialized_block_env.number = U256::from(5);
+
+        let err = build_empty_payload(config).unwrap_err();
+        assert!(matches!(err, PayloadBuilderError::BlockNumberMismatch { expected: 2, got: 5 }));
+    }
+
+    #[test]
+    fn empty_payload_exposes_bundle_state() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let recipient = Address::with_last_byte(1);
+        let mut config = mock_config(chain_spec, None);
+        config.attributes.withdrawals = Withdrawals::new(vec![Withdrawal {
+            index: 0,
+            validator_index: 0,
+            address: recipient,
+            amount: 2,
+        }]);
+
+        let captured = Arc::new(Mutex::new(None));
+        let hook = {
+            let captured = Arc::clone(&captured);
+            BundleStateHook::new(move |bundle| {
+                *captured.lock().unwrap() = Some(bundle.clone());
+            })
+        };
+        build_empty_payload(config.with_debug_bundle_state(hook)).unwrap();
+
+        let bundle = captured.lock().unwrap().take().expect("hook was not invoked");
+        let accounts = &bundle.state().state;
+        assert_eq!(accounts.len(), 1);
+        let account = accounts[&recipient].info.as_ref().unwrap();
+        assert_eq!(account.balance, U256::from(2 * GWEI_TO_WEI));
+    }
+
+    #[test]
+    fn empty_payload_bundle_retention() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let mut config = mock_config(chain_spec, None);
+        config.attributes.withdrawals = Withdrawals::new(vec![Withdrawal {
+            index: 0,
+            validator_index: 0,
+            address: Address::with_last_byte(1),
+            amount: 2,
+        }]);
+
+        let reverts = |retention| {
+            let captured = Arc::new(Mutex::new(None));
+            let hook = {
+                let captured = Arc::clone(&captured);
+                BundleStateHook::new(move |bundle| {
+                    *captured.lock().unwrap() = Some(bundle.clone());
+                })
+            };
+            let config =
+                config.clone().with_debug_bundle_state(hook).with_bundle_retention(retention);
+            build_empty_payload(config).unwrap();
+            let bundle = captured.lock().unwrap().take().expect("hook was not invoked");
+            bundle.state().reverts.len()
+        };
+
+        assert_eq!(reverts(BundleRetention::PlainState), 0);
+        assert_eq!(reverts(BundleRetention::Reverts), 1);
+    }
+
+    #[test]
+    fn empty_payload_reads_through_cached_reads() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let recipient = Address::with_last_byte(1);
+        let mut config = mock_config(chain_spec, None);
+        config.attributes.withdrawals = Withdrawals::new(vec![Withdrawal {
+            index: 0,
+            validator_index: 0,
+            address: recipient,
+            amount: 2,
+        }]);
+
+        let recipient_nonce = |cached_reads: Option<&mut CachedReads>| {
+            let captured = Arc::new(Mutex::new(None));
+            let hook = {
+                let captured = Arc::clone(&captured);
+                BundleStateHook::new(move |bundle| {
+                    *captured.lock().unwrap() = Some(bundle.clone());
+                })
+            };
+            let config = config.clone().with_debug_bundle_state(hook);
+            let client = MockEthProvider::default();
+            match cached_reads {
+                Some(cached_reads) => <EthereumPayloadBuilder as PayloadBuilder<
+                    NoopTransactionPool,
+                    MockEthProvider,
+                >>::build_empty_payload_with_cached_reads(
+                    &client, config, cached_reads
+                ),
+                None => <EthereumPayloadBuilder as PayloadBuilder<
+                    NoopTransactionPool,
+                    MockEthProvider,
+                >>::build_empty_payload(&client, config),
+            }
+            .unwrap();
+            let bundle = captured.lock().unwrap().take().expect("hook was not invoked");
+            bundle.state().state[&recipient].info.as_ref().unwrap().nonce
+        };
+
+        // the recipient is only known to the cached reads
+        let mut cached_reads = CachedReads::default();
+        cached_reads.insert_account(
+            recipient,
+            AccountInfo { nonce: 7, ..Default::default() },
+            Default::default(),
+        );
+        assert_eq!(recipient_nonce(Some(&mut cached_reads)), 7);
+        assert_eq!(recipient_nonce(None), 0);
+
+        // reads of the empty payload are cached for the next build
+        let mut cached_reads = CachedReads::default();
+        recipient_nonce(Some(&mut cached_reads));
+        assert!(cached_reads.as_db(EmptyDB::default()).basic_ref(recipient).unwrap().is_some());
+    }
+}
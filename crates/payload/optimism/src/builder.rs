@@ -5,22 +5,22 @@ use crate::{
     payload::{OptimismBuiltPayload, OptimismPayloadBuilderAttributes},
 };
 use reth_basic_payload_builder::*;
-use reth_payload_builder::error::PayloadBuilderError;
+use reth_payload_builder::{database::CachedReads, error::PayloadBuilderError};
 use reth_primitives::{
     constants::{BEACON_NONCE, EMPTY_RECEIPTS, EMPTY_TRANSACTIONS},
-    eip4844::calculate_excess_blob_gas,
     proofs,
     revm::env::tx_env_with_recovered,
     Block, ChainSpec, Hardfork, Header, IntoRecoveredTransaction, Receipt, Receipts, TxType,
     EMPTY_OMMER_ROOT_HASH, U256,
 };
-use reth_provider::{BundleStateWithReceipts, StateProviderFactory};
+use reth_provider::{
+    BundleStateWithReceipts, ProviderError, StateProviderBox, StateProviderFactory,
+};
 use reth_revm::database::StateProviderDatabase;
 use reth_transaction_pool::{BestTransactionsAttributes, TransactionPool};
 use revm::{
-    db::states::bundle_state::BundleRetention,
     primitives::{EVMError, EnvWithHandlerCfg, InvalidTransaction, ResultAndState},
-    DatabaseCommit, State,
+    Database, DatabaseCommit, State,
 };
 use std::sync::Arc;
 use tracing::{debug, trace, warn};
@@ -102,31 +102,77 @@ where
         client: &Client,
         config: PayloadConfig<Self::Attributes>,
     ) -> Result<OptimismBuiltPayload, PayloadBuilderError> {
-        let extra_data = config.extra_data();
-        let PayloadConfig {
-            initialized_block_env,
-            parent_block,
-            attributes,
-            chain_spec,
-            initialized_cfg,
-            ..
-        } = config;
-
-        debug!(target: "payload_builder", parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building empty payload");
-
-        let state = client.state_by_block_hash(parent_block.hash()).map_err(|err| {
-                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to get state for empty payload");
-                err
-            })?;
-        let mut db = State::builder()
-            .with_database_boxed(Box::new(StateProviderDatabase::new(&state)))
-            .with_bundle_update()
-            .build();
+        build_empty_payload_on_parent(client, config, None)
+    }
+
+    fn build_empty_payload_with_cached_reads(
+        client: &Client,
+        config: PayloadConfig<Self::Attributes>,
+        cached_reads: &mut CachedReads,
+    ) -> Result<OptimismBuiltPayload, PayloadBuilderError> {
+        build_empty_payload_on_parent(client, config, Some(cached_reads))
+    }
+}
 
-        let base_fee = initialized_block_env.basefee.to::<u64>();
-        let block_number = initialized_block_env.number.to::<u64>();
-        let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+/// Builds an empty payload on the state of the parent block, through the given [CachedReads] if
+/// any.
+fn build_empty_payload_on_parent<Client: StateProviderFactory>(
+    client: &Client,
+    config: PayloadConfig<OptimismPayloadBuilderAttributes>,
+    cached_reads: Option<&mut CachedReads>,
+) -> Result<OptimismBuiltPayload, PayloadBuilderError> {
+    let block_number = config.block_number().map_err(|err| {
+        warn!(target: "payload_builder", %err, "inconsistent block number for empty payload");
+        err
+    })?;
 
+    debug!(target: "payload_builder", parent_hash = ?config.parent_block.hash(), parent_number = config.parent_block.number, "building empty payload");
+
+    let state = parent_state_provider(client, &config.parent_block, config.latest_state_fallback)
+        .map_err(|err| {
+            warn!(target: "payload_builder", parent_hash=%config.parent_block.hash(), %err, "failed to get state for empty payload");
+            err
+        })?;
+    let state_db = StateProviderDatabase::new(&state);
+    match cached_reads {
+        Some(cached_reads) => {
+            let db = cached_payload_state(cached_reads, &state_db);
+            build_empty_payload_on_state(db, &state, config, block_number)
+        }
+        None => {
+            let db = State::builder().with_database_ref(&state_db).with_bundle_update().build();
+            build_empty_payload_on_state(db, &state, config, block_number)
+        }
+    }
+}
+
+/// Builds the empty payload of the given config with the given database of the parent state.
+///
+/// The `state` provider of the parent block is used to compute the state root.
+fn build_empty_payload_on_state<DB: Database<Error = ProviderError>>(
+    mut db: State<DB>,
+    state: &StateProviderBox,
+    config: PayloadConfig<OptimismPayloadBuilderAttributes>,
+    block_number: u64,
+) -> Result<OptimismBuiltPayload, PayloadBuilderError> {
+    let extra_data = config.extra_data();
+    let PayloadConfig {
+        initialized_block_env,
+        parent_block,
+        attributes,
+        chain_spec,
+        initialized_cfg,
+        debug_bundle_state,
+        bundle_retention,
+        ..
+    } = config;
+
+    let base_fee = initialized_block_env.basefee.to::<u64>();
+    let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+
+    let parent_beacon_block_root = if chain_spec
+        .is_cancun_active_at_timestamp(attributes.payload_attributes.timestamp)
+    {
         // apply eip-4788 pre block contract call
         pre_block_beacon_root_contract_call(
                 &mut db,
@@ -139,75 +185,81 @@ where
                 warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to apply beacon root contract call for empty payload");
                 err
             })?;
+        attributes.payload_attributes.parent_beacon_block_root
+    } else {
+        // the parent beacon block root must not be set before cancun
+        if let Some(root) = attributes.payload_attributes.parent_beacon_block_root {
+            warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %root, "ignoring parent beacon block root before cancun for empty payload");
+        }
+        None
+    };
 
-        let WithdrawalsOutcome { withdrawals_root, withdrawals } =
-                commit_withdrawals(&mut db, &chain_spec, attributes.payload_attributes.timestamp, attributes.payload_attributes.withdrawals.clone()).map_err(|err| {
-                    warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to commit withdrawals for empty payload");
-                    err
-                })?;
-
-        // merge all transitions into bundle state, this would apply the withdrawal balance
-        // changes and 4788 contract call
-        db.merge_transitions(BundleRetention::PlainState);
-
-        // calculate the state root
-        let bundle_state = db.take_bundle();
-        let state_root = state.state_root(&bundle_state).map_err(|err| {
-                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to calculate state root for empty payload");
+    let WithdrawalsOutcome { withdrawals_root, withdrawals } =
+            commit_withdrawals(&mut db, &chain_spec, attributes.payload_attributes.timestamp, attributes.payload_attributes.withdrawals.clone()).map_err(|err| {
+                warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to commit withdrawals for empty payload");
                 err
             })?;
 
-        let mut excess_blob_gas = None;
-        let mut blob_gas_used = None;
-
-        if chain_spec.is_cancun_active_at_timestamp(attributes.payload_attributes.timestamp) {
-            excess_blob_gas = if chain_spec.is_cancun_active_at_timestamp(parent_block.timestamp) {
-                let parent_excess_blob_gas = parent_block.excess_blob_gas.unwrap_or_default();
-                let parent_blob_gas_used = parent_block.blob_gas_used.unwrap_or_default();
-                Some(calculate_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used))
-            } else {
-                // for the first post-fork block, both parent.blob_gas_used and
-                // parent.excess_blob_gas are evaluated as 0
-                Some(calculate_excess_blob_gas(0, 0))
-            };
+    // merge all transitions into bundle state, this would apply the withdrawal balance
+    // changes and 4788 contract call
+    db.merge_transitions(bundle_retention);
 
-            blob_gas_used = Some(0);
-        }
+    // calculate the state root
+    let bundle_state = db.take_bundle();
+    if let Some(hook) = &debug_bundle_state {
+        hook.call(&BundleStateWithReceipts::new(
+            bundle_state.clone(),
+            Receipts::from_vec(vec![Vec::new()]),
+            block_number,
+        ));
+    }
+    let state_root = state.state_root(&bundle_state).map_err(|err| {
+            warn!(target: "payload_builder", parent_hash=%parent_block.hash(), %err, "failed to calculate state root for empty payload");
+            err
+        })?;
 
-        let header = Header {
-            parent_hash: parent_block.hash(),
-            ommers_hash: EMPTY_OMMER_ROOT_HASH,
-            beneficiary: initialized_block_env.coinbase,
-            state_root,
-            transactions_root: EMPTY_TRANSACTIONS,
-            withdrawals_root,
-            receipts_root: EMPTY_RECEIPTS,
-            logs_bloom: Default::default(),
-            timestamp: attributes.payload_attributes.timestamp,
-            mix_hash: attributes.payload_attributes.prev_randao,
-            nonce: BEACON_NONCE,
-            base_fee_per_gas: Some(base_fee),
-            number: parent_block.number + 1,
-            gas_limit: block_gas_limit,
-            difficulty: U256::ZERO,
-            gas_used: 0,
-            extra_data,
-            blob_gas_used,
-            excess_blob_gas,
-            parent_beacon_block_root: attributes.payload_attributes.parent_beacon_block_root,
-        };
+    let mut excess_blob_gas = None;
+    let mut blob_gas_used = None;
 
-        let block = Block { header, body: vec![], ommers: vec![], withdrawals };
-        let sealed_block = block.seal_slow();
+    if chain_spec.is_cancun_active_at_timestamp(attributes.payload_attributes.timestamp) {
+        excess_blob_gas = Some(compute_excess_blob_gas(&chain_spec, &parent_block));
 
-        Ok(OptimismBuiltPayload::new(
-            attributes.payload_attributes.payload_id(),
-            sealed_block,
-            U256::ZERO,
-            chain_spec,
-            attributes,
-        ))
+        blob_gas_used = Some(0);
     }
+
+    let header = Header {
+        parent_hash: parent_block.hash(),
+        ommers_hash: EMPTY_OMMER_ROOT_HASH,
+        beneficiary: initialized_block_env.coinbase,
+        state_root,
+        transactions_root: EMPTY_TRANSACTIONS,
+        withdrawals_root,
+        receipts_root: EMPTY_RECEIPTS,
+        logs_bloom: Default::default(),
+        timestamp: attributes.payload_attributes.timestamp,
+        mix_hash: attributes.payload_attributes.prev_randao,
+        nonce: BEACON_NONCE,
+        base_fee_per_gas: Some(base_fee),
+        number: parent_block.number + 1,
+        gas_limit: block_gas_limit,
+        difficulty: U256::ZERO,
+        gas_used: 0,
+        extra_data,
+        blob_gas_used,
+        excess_blob_gas,
+        parent_beacon_block_root,
+    };
+
+    let block = Block { header, body: vec![], ommers: vec![], withdrawals };
+    let sealed_block = block.seal_slow();
+
+    Ok(OptimismBuiltPayload::new(
+        attributes.payload_attributes.payload_id(),
+        sealed_block,
+        U256::ZERO,
+        chain_spec,
+        attributes,
+    ))
 }
 
 /// Constructs an Ethereum transaction payload from the transactions sent through the
@@ -240,14 +292,17 @@ where
         parent_block,
         attributes,
         chain_spec,
+        forced_top_of_block,
+        debug_bundle_state,
+        bundle_retention,
+        debug_tx_traces,
+        max_transactions,
         ..
     } = config;
 
     debug!(target: "payload_builder", id=%attributes.payload_attributes.payload_id(), parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building new payload");
     let mut cumulative_gas_used = 0;
-    let block_gas_limit: u64 = attributes
-        .gas_limit
-        .unwrap_or_else(|| initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX));
+    let block_gas_limit: u64 = initialized_block_env.gas_limit.try_into().unwrap_or(u64::MAX);
     let base_fee = initialized_block_env.basefee.to::<u64>();
 
     let mut executed_txs = Vec::new();
@@ -325,16 +380,14 @@ where
                 ))
             })?;
 
-        let mut evm = revm::Evm::builder()
-            .with_db(&mut db)
-            .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
-                initialized_cfg.clone(),
-                initialized_block_env.clone(),
-                tx_env_with_recovered(&sequencer_tx),
-            ))
-            .build();
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            tx_env_with_recovered(&sequencer_tx),
+        );
 
-        let ResultAndState { result, state } = match evm.transact() {
+        let res = transact(&mut db, env, sequencer_tx.hash, debug_tx_traces.as_ref());
+        let ResultAndState { result, state } = match res {
             Ok(res) => res,
             Err(err) => {
                 match err {
@@ -350,8 +403,6 @@ where
             }
         };
 
-        // to release the db reference drop evm.
-        drop(evm);
         // commit changes
         db.commit(state);
 
@@ -382,8 +433,59 @@ where
         executed_txs.push(sequencer_tx.into_signed());
     }
 
+    // the forced transactions are included in order after the sequencer transactions and
+    // before any pool transaction, also if the pool is ignored
+    let remaining_gas = block_gas_limit.saturating_sub(cumulative_gas_used);
+    for tx in recover_forced_top_of_block(forced_top_of_block, remaining_gas)? {
+        // check if the job was cancelled, if so we can exit early
+        if cancel.is_cancelled() {
+            return Ok(BuildOutcome::Cancelled)
+        }
+
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            initialized_cfg.clone(),
+            initialized_block_env.clone(),
+            tx_env_with_recovered(&tx),
+        );
+
+        let res = transact(&mut db, env, tx.hash, debug_tx_traces.as_ref());
+        // a forced transaction that can't be executed fails the build, since it must be
+        // included
+        let ResultAndState { result, state } =
+            res.map_err(PayloadBuilderError::EvmExecutionError)?;
+        db.commit(state);
+
+        let gas_used = result.gas_used();
+        cumulative_gas_used += gas_used;
+
+        receipts.push(Some(Receipt {
+            tx_type: tx.tx_type(),
+            success: result.is_success(),
+            cumulative_gas_used,
+            logs: result.into_logs().into_iter().map(Into::into).collect(),
+            deposit_nonce: None,
+            deposit_receipt_version: None,
+        }));
+
+        let miner_fee = tx
+            .effective_tip_per_gas(Some(base_fee))
+            .expect("fee is always valid; execution succeeded");
+        total_fees += U256::from(miner_fee) * U256::from(gas_used);
+
+        executed_txs.push(tx.into_signed());
+    }
+
     if !attributes.no_tx_pool {
+        // the sequencer and forced transactions don't count towards the maximum number of
+        // transactions
+        let top_of_block_txs = executed_txs.len();
         while let Some(pool_tx) = best_txs.next() {
+            // stop once the configured number of transactions is included
+            if max_transactions.is_some_and(|max| executed_txs.len() - top_of_block_txs >= max) {
+                trace!(target: "payload_builder", ?max_transactions, "reached the maximum number of transactions");
+                break
+            }
+
             // ensure we still have capacity for this transaction
             if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
                 // we can't fit this transaction into the block, so we need to mark it as
@@ -409,16 +511,14 @@ where
             let tx = pool_tx.to_recovered_transaction();
 
             // Configure the environment for the block.
-            let mut evm = revm::Evm::builder()
-                .with_db(&mut db)
-                .with_env_with_handler_cfg(EnvWithHandlerCfg::new_with_cfg_env(
-                    initialized_cfg.clone(),
-                    initialized_block_env.clone(),
-                    tx_env_with_recovered(&tx),
-                ))
-                .build();
+            let env = EnvWithHandlerCfg::new_with_cfg_env(
+                initialized_cfg.clone(),
+                initialized_block_env.clone(),
+                tx_env_with_recovered(&tx),
+            );
 
-            let ResultAndState { result, state } = match evm.transact() {
+            let res = transact(&mut db, env, tx.hash, debug_tx_traces.as_ref());
+            let ResultAndState { result, state } = match res {
                 Ok(res) => res,
                 Err(err) => {
                     match err {
@@ -442,8 +542,6 @@ where
                     }
                 }
             };
-            // drop evm so db is released.
-            drop(evm);
             // commit changes
             db.commit(state);
 
@@ -489,7 +587,7 @@ where
 
     // merge all transitions into bundle state, this would apply the withdrawal balance changes
     // and 4788 contract call
-    db.merge_transitions(BundleRetention::PlainState);
+    db.merge_transitions(bundle_retention);
 
     let bundle = BundleStateWithReceipts::new(
         db.take_bundle(),
@@ -505,6 +603,10 @@ where
         .expect("Number is in range");
     let logs_bloom = bundle.block_logs_bloom(block_number).expect("Number is in range");
 
+    if let Some(hook) = &debug_bundle_state {
+        hook.call(&bundle);
+    }
+
     // calculate the state root
     let state_root = state_provider.state_root(bundle.state())?;
 
@@ -518,15 +620,7 @@ where
 
     // only determine cancun fields when active
     if chain_spec.is_cancun_active_at_timestamp(attributes.payload_attributes.timestamp) {
-        excess_blob_gas = if chain_spec.is_cancun_active_at_timestamp(parent_block.timestamp) {
-            let parent_excess_blob_gas = parent_block.excess_blob_gas.unwrap_or_default();
-            let parent_blob_gas_used = parent_block.blob_gas_used.unwrap_or_default();
-            Some(calculate_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used))
-        } else {
-            // for the first post-fork block, both parent.blob_gas_used and
-            // parent.excess_blob_gas are evaluated as 0
-            Some(calculate_excess_blob_gas(0, 0))
-        };
+        excess_blob_gas = Some(compute_excess_blob_gas(&chain_spec, &parent_block));
 
         blob_gas_used = Some(0);
     }
@@ -573,3 +667,46 @@ where
 
     Ok(BuildOutcome::Better { payload, cached_reads })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_payload_builder::{EthPayloadBuilderAttributes, PayloadId};
+    use reth_primitives::{Bytes, ChainSpecBuilder, B256};
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_transaction_pool::noop::NoopTransactionPool;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn empty_payload_drops_parent_beacon_block_root_before_cancun() {
+        let chain_spec =
+            Arc::new(ChainSpecBuilder::mainnet().without_fork(Hardfork::Cancun).build());
+        let parent = Block {
+            header: Header { number: 1, gas_limit: 30_000_000, ..Default::default() },
+            ..Default::default()
+        }
+        .seal_slow();
+        let attributes = OptimismPayloadBuilderAttributes {
+            payload_attributes: EthPayloadBuilderAttributes {
+                id: PayloadId::new([1; 8]),
+                parent: parent.hash(),
+                timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                suggested_fee_recipient: Default::default(),
+                prev_randao: B256::ZERO,
+                withdrawals: Default::default(),
+                parent_beacon_block_root: Some(B256::random()),
+            },
+            no_tx_pool: false,
+            transactions: Vec::new(),
+            gas_limit: None,
+        };
+        let config = PayloadConfig::new(Arc::new(parent), Bytes::default(), attributes, chain_spec);
+
+        let payload = <OptimismPayloadBuilder as PayloadBuilder<
+            NoopTransactionPool,
+            MockEthProvider,
+        >>::build_empty_payload(&MockEthProvider::default(), config)
+        .unwrap();
+        assert_eq!(payload.block().parent_beacon_block_root, None);
+    }
+}
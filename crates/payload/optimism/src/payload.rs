@@ -106,6 +106,10 @@ impl PayloadBuilderAttributes for OptimismPayloadBuilderAttributes {
         &self.payload_attributes.withdrawals
     }
 
+    fn gas_limit(&self) -> Option<u64> {
+        self.gas_limit
+    }
+
     fn cfg_and_block_env(
         &self,
         chain_spec: &ChainSpec,